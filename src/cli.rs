@@ -0,0 +1,156 @@
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, Subcommand};
+use creature_simulation::{DisplayMode, Scenario};
+
+#[derive(Parser)]
+#[command(about = "An experiment in artificial life and evolution")]
+pub struct Cli {
+    /// Path to the log4rs configuration file.
+    #[arg(long, global = true, default_value = "src/log4rs.yaml")]
+    pub log_config: String,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Evolve a population and replay the final generation.
+    Run {
+        #[command(flatten)]
+        simulation: SimulationArgs,
+
+        /// How the final generation is displayed.
+        #[arg(long, value_enum, default_value = "dynamic")]
+        display: DisplayMode,
+
+        /// Delay between frames in milliseconds, from 10 to 1000.
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(10..=1000))]
+        frame_delay: u64,
+    },
+    /// Play back a replay file recorded with --record.
+    Replay {
+        /// The replay file to open.
+        file: String,
+
+        /// How the replay is displayed.
+        #[arg(long, value_enum, default_value = "dynamic")]
+        display: DisplayMode,
+
+        /// Delay between frames in milliseconds, from 10 to 1000.
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(10..=1000))]
+        frame_delay: u64,
+    },
+    /// Evolve a population without any display.
+    Headless {
+        #[command(flatten)]
+        simulation: SimulationArgs,
+
+        /// Write one row of statistics per generation to this CSV file.
+        #[arg(long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Args)]
+pub struct SimulationArgs {
+    /// Scenario file to start from. Options given on the command line override it.
+    #[arg(long)]
+    pub scenario: Option<String>,
+
+    /// Name shown in the title of the world.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Width of the world in cells.
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub width: Option<usize>,
+
+    /// Height of the world in cells.
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub height: Option<usize>,
+
+    /// Level file to build the terrain and plants from: ASCII text, PNG or PPM.
+    /// Its size replaces the width and height.
+    #[arg(long)]
+    pub level: Option<String>,
+
+    /// Number of creatures each generation starts with.
+    #[arg(long)]
+    pub carrying_capacity: Option<usize>,
+
+    /// Number of generations to evolve.
+    #[arg(long)]
+    pub generations: Option<usize>,
+
+    /// Number of simulation steps in each generation.
+    #[arg(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub steps: Option<usize>,
+
+    /// Seed for the random number generator. A random seed is used if omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Start from the creatures in this world snapshot.
+    #[arg(long)]
+    pub population: Option<String>,
+
+    /// Save a snapshot of the final world to this file.
+    #[arg(long)]
+    pub save: Option<String>,
+
+    /// Record the history of the final generation to this replay file.
+    /// With --record-generations, each listed generation is written as soon as
+    /// it ends, to this path with the generation number added, e.g. run-50.json.
+    #[arg(long)]
+    pub record: Option<String>,
+
+    /// Comma separated generations, counted from 0, to record instead of the
+    /// final one.
+    #[arg(long, value_delimiter = ',', requires = "record")]
+    pub record_generations: Vec<usize>,
+
+    /// Write the family tree of every creature to this file in Newick format.
+    #[arg(long)]
+    pub newick: Option<String>,
+
+    /// Write the family tree of every creature to this file as a Graphviz DOT graph.
+    #[arg(long)]
+    pub dot: Option<String>,
+}
+
+impl SimulationArgs {
+    pub fn scenario(&self) -> Scenario {
+        let mut scenario = match &self.scenario {
+            Some(path) => Scenario::load(path)
+                .unwrap_or_else(|e| panic!("Could not load scenario {}: {}", path, e)),
+            None => Scenario::default(),
+        };
+        if let Some(name) = &self.name {
+            scenario.world.name = name.clone();
+        }
+        if let Some(width) = self.width {
+            scenario.world.width = width;
+        }
+        if let Some(height) = self.height {
+            scenario.world.height = height;
+        }
+        if let Some(level) = &self.level {
+            scenario.world.level = Some(level.clone());
+        }
+        if let Some(carrying_capacity) = self.carrying_capacity {
+            scenario.world.carrying_capacity = carrying_capacity;
+            scenario.population.size = carrying_capacity;
+        }
+        if let Some(generations) = self.generations {
+            scenario.schedule.generations = generations;
+        }
+        if let Some(steps) = self.steps {
+            scenario.schedule.steps = steps;
+        }
+        if let Some(population) = &self.population {
+            scenario.population.snapshot = Some(population.clone());
+        }
+        scenario
+    }
+}
@@ -1,39 +1,124 @@
 use crate::genome::Genome;
+use crate::movement::MovementModel;
 use crate::position::Position;
+use crate::predation::{Diet, PredationConfig};
+use crate::terrain::Terrain;
+use crate::topology::Topology;
+use crate::traits::{TraitEffect, TraitRegistry};
+use crate::world::WorldConfig;
 use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::slice::Iter;
 
-#[derive(Clone, Copy, Debug)]
+/*
+ * An instruction of a behavior pattern. The If instructions read a sensor
+ * and run the instruction after them only if the sensor reads true. Attack
+ * does nothing unless the creature is a carnivore.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
     MoveForward,
     TurnLeft,
     TurnRandom,
     TurnRight,
+    IfPlantAhead,
+    IfWallAhead,
+    IfCreatureAhead,
+    IfLifeBelow,
+    Attack,
 }
 
 impl Action {
     pub fn iterator() -> Iter<'static, Self> {
-        static ACTION: [Action; 4] = [
+        static ACTION: [Action; 9] = [
             Action::MoveForward,
             Action::TurnLeft,
             Action::TurnRandom,
             Action::TurnRight,
+            Action::IfPlantAhead,
+            Action::IfWallAhead,
+            Action::IfCreatureAhead,
+            Action::IfLifeBelow,
+            Action::Attack,
         ];
         ACTION.iter()
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     North,
     South,
     East,
     West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
 }
 
-#[derive(Clone, Copy)]
+impl Direction {
+    #[must_use]
+    pub fn glyph(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::South => 'v',
+            Self::East => '>',
+            Self::West => '<',
+            Self::NorthEast => '↗',
+            Self::SouthEast => '↘',
+            Self::SouthWest => '↙',
+            Self::NorthWest => '↖',
+        }
+    }
+}
+
+/*
+ * Everything about the world that a creature needs to take its next step.
+ * The sensors look at the cell the creature is facing; an edge that cannot
+ * be crossed counts as a wall.
+ */
+pub struct Surroundings<'a> {
+    pub terrain: &'a Terrain,
+    pub traits: &'a TraitRegistry,
+    pub predation: &'a PredationConfig,
+    pub topology: Topology,
+    pub movement: MovementModel,
+    pub plant_is_here: bool,
+    pub plant_ahead: bool,
+    pub wall_ahead: bool,
+    pub creature_ahead: bool,
+}
+
+/*
+ * What a creature did over its life, used to score its fitness.
+ */
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub ticks_alive: u64,
+    pub plants_eaten: u32,
+    pub distance_travelled: u32,
+    pub prey_eaten: u32,
+}
+
+/*
+ * What a creature did in one step that the world has to carry out.
+ */
+#[derive(Clone, Copy, Default)]
+pub struct StepOutcome {
+    pub ate_plant: bool,
+    pub attacked: bool,
+}
+
+/*
+ * The strain of a creature is the id of the founder of its lineage, handed
+ * down from the first parent, and parents holds the ids of the creatures it
+ * was bred from. The species is assigned by the world from the genome.
+ */
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Creature {
     id: u64,
     pub name: [char; 15],
@@ -41,10 +126,16 @@ pub struct Creature {
     pub direction: Direction,
     pub position: Position,
     pub genome: Genome,
-    program_counter: usize,
+    pub(crate) program_counter: usize,
     pub world_bounds: Position,
     generation: u64,
     pub strain: u64,
+    #[serde(default)]
+    pub parents: Vec<u64>,
+    #[serde(default)]
+    pub stats: LifetimeStats,
+    #[serde(default)]
+    pub species: u64,
 }
 
 fn array_from_str(string: &str) -> [char; 15] {
@@ -90,87 +181,150 @@ impl Creature {
         position: Position,
         world_bounds: Position,
         name: &str,
+        genome: Genome,
+        direction: Direction,
         generation: u64,
         id: u64,
     ) -> Self {
-        let directions = vec![
-            Direction::North,
-            Direction::South,
-            Direction::East,
-            Direction::West,
-        ];
-        let direction: Direction = *directions
-            .choose(&mut rand::thread_rng())
-            .expect("Could not select random direction.");
         Self {
             id,
             name: array_from_str(name),
             life: 255.0,
             position,
             direction,
-            genome: Genome::new_even_distribution(),
+            genome,
             program_counter: 0,
             world_bounds,
             generation,
             strain: id,
+            parents: Vec::new(),
+            stats: LifetimeStats::default(),
+            species: 0,
         }
     }
 
+    /*
+     * The offspring inherits the genome of its parent, mutated according to
+     * the mutation rates of the world.
+     */
     #[must_use]
     pub fn new_from_old(
         creature: &Self,
         id: u64,
         position: Position,
-        world_bounds: Position,
+        config: &WorldConfig,
+        rng: &mut impl Rng,
     ) -> Self {
-        let directions = vec![
-            Direction::North,
-            Direction::South,
-            Direction::East,
-            Direction::West,
-        ];
-        let direction: Direction = *directions
-            .choose(&mut rand::thread_rng())
-            .expect("Could not select random direction.");
+        let genome = creature.genome.clone();
+        Self::offspring(
+            creature,
+            genome,
+            vec![creature.id],
+            id,
+            position,
+            config,
+            rng,
+        )
+    }
+
+    /*
+     * The offspring recombines the genomes of both parents with the
+     * crossover operator of the world before it is mutated. It takes its
+     * name from the first parent.
+     */
+    #[must_use]
+    pub fn new_from_parents(
+        a: &Self,
+        b: &Self,
+        id: u64,
+        position: Position,
+        config: &WorldConfig,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let genome = Genome::crossover(
+            &a.genome,
+            &b.genome,
+            config.crossover,
+            &config.behavior,
+            rng,
+        );
+        let generation = a.generation.max(b.generation);
+        let parents = if a.id == b.id {
+            vec![a.id]
+        } else {
+            vec![a.id, b.id]
+        };
+        let mut child = Self::offspring(a, genome, parents, id, position, config, rng);
+        child.generation = generation + 1;
+        child
+    }
+
+    fn offspring(
+        parent: &Self,
+        mut genome: Genome,
+        parents: Vec<u64>,
+        id: u64,
+        position: Position,
+        config: &WorldConfig,
+        rng: &mut impl Rng,
+    ) -> Self {
+        genome.mutate(&config.traits, &config.behavior, &config.mutation, rng);
         Self {
             id,
-            name: creature.name,
+            name: parent.name,
             life: 255.0,
             position,
-            direction,
-            genome: creature.genome,
+            direction: config.movement.random_direction(rng),
+            genome,
             program_counter: 0,
-            world_bounds,
-            generation: creature.generation + 1,
-            strain: id,
+            world_bounds: Position::new(config.width as i32, config.height as i32),
+            generation: parent.generation + 1,
+            strain: parent.strain,
+            parents,
+            stats: LifetimeStats::default(),
+            species: 0,
         }
     }
 
-    pub fn move_forward(&mut self) {
-        match self.direction {
-            Direction::North => self.move_relative(0, 1),
-            Direction::South => self.move_relative(0, -1),
-            Direction::East => self.move_relative(1, 0),
-            Direction::West => self.move_relative(-1, 0),
+    /*
+     * Walls stop the creature where it is. Any other cell is entered, and its
+     * movement cost is taken from the creature's life.
+     */
+    pub fn move_forward(&mut self, terrain: &Terrain, topology: Topology, movement: MovementModel) {
+        let Some(target) = self.ahead(topology, movement) else {
+            if topology == Topology::Reflect {
+                self.turn_around(movement);
+            }
+            return;
+        };
+        if terrain.get(target).blocks() {
+            return;
         }
+        self.position = target;
+        self.life = (self.life - terrain.move_cost(self.position)).max(0.0);
+        self.stats.distance_travelled += 1;
     }
 
-    pub fn turn_right(&mut self) {
-        match self.direction {
-            Direction::North => self.direction = Direction::East,
-            Direction::South => self.direction = Direction::West,
-            Direction::East => self.direction = Direction::South,
-            Direction::West => self.direction = Direction::North,
-        }
+    pub fn turn_right(&mut self, movement: MovementModel) {
+        self.direction = movement.turn_right(self.direction);
     }
 
-    pub fn turn_left(&mut self) {
-        match self.direction {
-            Direction::North => self.direction = Direction::West,
-            Direction::South => self.direction = Direction::East,
-            Direction::East => self.direction = Direction::North,
-            Direction::West => self.direction = Direction::South,
-        }
+    pub fn turn_around(&mut self, movement: MovementModel) {
+        self.direction = movement.turn_around(self.direction);
+    }
+
+    pub fn turn_left(&mut self, movement: MovementModel) {
+        self.direction = movement.turn_left(self.direction);
+    }
+
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
     #[must_use]
@@ -178,57 +332,157 @@ impl Creature {
         self.life > 0.0
     }
 
-    pub fn step(&mut self, plant_is_here: bool) {
+    #[must_use]
+    pub fn diet(&self, traits: &TraitRegistry, predation: &PredationConfig) -> Diet {
+        predation.diet(self.trait_effect(traits, TraitEffect::Diet))
+    }
+
+    /*
+     * Herbivores eat the plant they are standing on. An attack is only
+     * reported here, since finding the prey needs the rest of the world.
+     */
+    pub fn step(&mut self, surroundings: &Surroundings, rng: &mut impl Rng) -> StepOutcome {
+        let mut outcome = StepOutcome::default();
         if self.is_alive() {
-            if plant_is_here {
-                self.life += self.genome.trait_set.eating_efficiency.get_value();
+            let diet = self.diet(surroundings.traits, surroundings.predation);
+            if surroundings.plant_is_here && diet == Diet::Herbivore {
+                self.life += self.trait_effect(surroundings.traits, TraitEffect::EatingEfficiency);
                 if self.life > 255.0 {
                     self.life = 255.0;
                 }
+                outcome.ate_plant = true;
+                self.stats.plants_eaten += 1;
             }
+            outcome.attacked = self.run_program(surroundings, rng) == Some(Action::Attack)
+                && diet == Diet::Carnivore;
+            self.age(surroundings.traits);
+            self.stats.ticks_alive += 1;
+        }
+        outcome
+    }
+
+    /*
+     * Feeds the creature on prey that had the given life.
+     */
+    pub fn eat_prey(&mut self, prey_life: f64, predation: &PredationConfig) {
+        self.life = (self.life + prey_life * predation.efficiency).min(255.0);
+        self.stats.prey_eaten += 1;
+    }
+
+    /*
+     * Reading a sensor takes no time, so instructions are evaluated until
+     * one action has been taken. A sensor that reads false skips the
+     * instruction after it. If every instruction is visited without taking
+     * an action, the creature does nothing this step. Returns the action
+     * taken.
+     */
+    fn run_program(&mut self, surroundings: &Surroundings, rng: &mut impl Rng) -> Option<Action> {
+        let length = self.genome.behavior.action_pattern.len();
+        for _ in 0..length {
             let action = self.genome.behavior.action_pattern[self.program_counter];
-            match action {
-                Action::MoveForward => self.move_forward(),
-                Action::TurnLeft => self.turn_left(),
-                Action::TurnRandom => self.random_turn(),
-                Action::TurnRight => self.turn_right(),
-            }
-            self.program_counter += 1;
-            if self.program_counter > 4 {
-                self.program_counter = 0;
+            self.program_counter = (self.program_counter + 1) % length;
+            let condition = match action {
+                Action::MoveForward => {
+                    self.move_forward(
+                        surroundings.terrain,
+                        surroundings.topology,
+                        surroundings.movement,
+                    );
+                    return Some(action);
+                }
+                Action::TurnLeft => {
+                    self.turn_left(surroundings.movement);
+                    return Some(action);
+                }
+                Action::TurnRandom => {
+                    self.random_turn(surroundings.movement, rng);
+                    return Some(action);
+                }
+                Action::TurnRight => {
+                    self.turn_right(surroundings.movement);
+                    return Some(action);
+                }
+                Action::IfPlantAhead => surroundings.plant_ahead,
+                Action::IfWallAhead => surroundings.wall_ahead,
+                Action::IfCreatureAhead => surroundings.creature_ahead,
+                Action::IfLifeBelow => {
+                    self.life < self.trait_effect(surroundings.traits, TraitEffect::HungerThreshold)
+                }
+                Action::Attack => return Some(action),
+            };
+            if !condition {
+                self.program_counter = (self.program_counter + 1) % length;
             }
-            self.age();
         }
+        None
     }
 
-    pub fn move_relative(&mut self, x: i32, y: i32) {
-        self.position.x += x;
-        self.position.y += y;
+    /*
+     * A random neighbouring cell that can be entered and is not occupied, or
+     * None if the creature is walled in or surrounded.
+     */
+    pub fn free_neighbour(
+        &self,
+        terrain: &Terrain,
+        topology: Topology,
+        movement: MovementModel,
+        occupied: impl Fn(Position) -> bool,
+        rng: &mut impl Rng,
+    ) -> Option<Position> {
+        let mut directions = movement.directions().to_vec();
+        directions.shuffle(rng);
+        directions.into_iter().find_map(|direction| {
+            let (x, y) = movement.offset(self.position, direction);
+            self.target(x, y, topology)
+                .filter(|&target| !terrain.get(target).blocks() && !occupied(target))
+        })
+    }
 
-        if self.position.x > self.world_bounds.x {
-            self.position.x = 0;
-        }
-        if self.position.x < 0 {
-            self.position.x = self.world_bounds.x;
-        }
-        if self.position.y > self.world_bounds.y {
-            self.position.y = 0;
-        }
-        if self.position.y < 0 {
-            self.position.y = self.world_bounds.y;
+    /*
+     * The cell the creature is facing, or None if it lies beyond an edge
+     * that cannot be crossed.
+     */
+    #[must_use]
+    pub fn ahead(&self, topology: Topology, movement: MovementModel) -> Option<Position> {
+        let (x, y) = movement.offset(self.position, self.direction);
+        self.target(x, y, topology)
+    }
+
+    /*
+     * The cell at the given offset from the creature, or None if it lies
+     * beyond an edge that cannot be crossed. The world spans 0..world_bounds
+     * on both axes.
+     */
+    #[must_use]
+    pub fn target(&self, x: i32, y: i32, topology: Topology) -> Option<Position> {
+        topology.wrap(
+            Position::new(self.position.x + x, self.position.y + y),
+            self.world_bounds.x as usize,
+            self.world_bounds.y as usize,
+        )
+    }
+
+    pub fn move_relative(&mut self, x: i32, y: i32, topology: Topology) {
+        if let Some(target) = self.target(x, y, topology) {
+            self.position = target;
         }
     }
 
-    fn random_turn(&mut self) {
-        if rand::thread_rng().gen_range(0..2) == 0 {
-            self.turn_right();
+    fn random_turn(&mut self, movement: MovementModel, rng: &mut impl Rng) {
+        if rng.gen_range(0..2) == 0 {
+            self.turn_right(movement);
         } else {
-            self.turn_left();
+            self.turn_left(movement);
         }
     }
 
-    fn age(&mut self) {
-        let n = 1.0 / self.genome.trait_set.aging_speed_divisor.get_value() + 1.0;
+    #[must_use]
+    pub fn trait_effect(&self, traits: &TraitRegistry, effect: TraitEffect) -> f64 {
+        traits.effect(effect, &self.genome.trait_set)
+    }
+
+    fn age(&mut self, traits: &TraitRegistry) {
+        let n = 1.0 / self.trait_effect(traits, TraitEffect::AgingSpeedDivisor) + 1.0;
         if self.life < n {
             self.life = 0.0;
             return;
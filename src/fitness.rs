@@ -0,0 +1,115 @@
+use crate::creature::Creature;
+use serde::{Deserialize, Serialize};
+
+/*
+ * Scores a creature at the end of a generation from what it did over its
+ * life. Selection strategies prefer creatures with higher scores.
+ */
+pub trait Fitness {
+    fn score(&self, creature: &Creature) -> f64;
+}
+
+/*
+ * The life the creature has left.
+ */
+#[derive(Clone, Default)]
+pub struct LifeFitness;
+
+impl Fitness for LifeFitness {
+    fn score(&self, creature: &Creature) -> f64 {
+        creature.life
+    }
+}
+
+/*
+ * The number of plants the creature ate.
+ */
+#[derive(Clone, Default)]
+pub struct PlantsEatenFitness;
+
+impl Fitness for PlantsEatenFitness {
+    fn score(&self, creature: &Creature) -> f64 {
+        f64::from(creature.stats.plants_eaten)
+    }
+}
+
+/*
+ * The number of ticks the creature was alive.
+ */
+#[derive(Clone, Default)]
+pub struct LifetimeFitness;
+
+impl Fitness for LifetimeFitness {
+    fn score(&self, creature: &Creature) -> f64 {
+        creature.stats.ticks_alive as f64
+    }
+}
+
+/*
+ * The number of cells the creature moved, which rewards exploration.
+ */
+#[derive(Clone, Default)]
+pub struct DistanceFitness;
+
+impl Fitness for DistanceFitness {
+    fn score(&self, creature: &Creature) -> f64 {
+        f64::from(creature.stats.distance_travelled)
+    }
+}
+
+/*
+ * A weighted sum of every statistic, for experiments that trade them off
+ * against each other. Negative weights penalise a statistic.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeightedFitness {
+    pub life: f64,
+    pub ticks_alive: f64,
+    pub plants_eaten: f64,
+    pub distance_travelled: f64,
+}
+
+impl Default for WeightedFitness {
+    fn default() -> Self {
+        Self {
+            life: 1.0,
+            ticks_alive: 0.0,
+            plants_eaten: 0.0,
+            distance_travelled: 0.0,
+        }
+    }
+}
+
+impl Fitness for WeightedFitness {
+    fn score(&self, creature: &Creature) -> f64 {
+        self.life * creature.life
+            + self.ticks_alive * creature.stats.ticks_alive as f64
+            + self.plants_eaten * f64::from(creature.stats.plants_eaten)
+            + self.distance_travelled * f64::from(creature.stats.distance_travelled)
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FitnessConfig {
+    #[default]
+    Life,
+    PlantsEaten,
+    Lifetime,
+    Distance,
+    Weighted(WeightedFitness),
+}
+
+impl FitnessConfig {
+    #[must_use]
+    pub fn build(&self) -> Box<dyn Fitness> {
+        match self {
+            Self::Life => Box::new(LifeFitness),
+            Self::PlantsEaten => Box::new(PlantsEatenFitness),
+            Self::Lifetime => Box::new(LifetimeFitness),
+            Self::Distance => Box::new(DistanceFitness),
+            Self::Weighted(fitness) => Box::new(fitness.clone()),
+        }
+    }
+}
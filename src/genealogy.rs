@@ -0,0 +1,242 @@
+use crate::creature::{string_from_array, Creature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/*
+ * What the genealogy remembers about one creature after its world is gone.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub name: String,
+    pub parents: Vec<u64>,
+    pub strain: u64,
+    pub generation: u64,
+}
+
+/*
+ * Every creature that lived in a simulation, across all generations, keyed
+ * by id. Creatures whose parents are not in the store, such as the first
+ * generation or creatures loaded from a snapshot, are the roots of the
+ * family tree.
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Genealogy {
+    records: BTreeMap<u64, Record>,
+}
+
+enum Visit {
+    Open(u64),
+    Close(u64),
+    Comma,
+}
+
+impl Genealogy {
+    pub fn record(&mut self, creature: &Creature) {
+        self.records.insert(
+            creature.id(),
+            Record {
+                name: string_from_array(creature.name).trim().to_string(),
+                parents: creature.parents.clone(),
+                strain: creature.strain,
+                generation: creature.generation(),
+            },
+        );
+    }
+
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /*
+     * The parent a creature hangs from in the tree: its first parent, if
+     * that parent is known.
+     */
+    fn tree_parent(&self, record: &Record) -> Option<u64> {
+        record
+            .parents
+            .first()
+            .copied()
+            .filter(|p| self.records.contains_key(p))
+    }
+
+    /*
+     * The family tree in Newick format, with creature ids as labels and
+     * generations as branch lengths. Children of sexual reproduction hang
+     * from their first parent only, since a Newick tree cannot show a
+     * second one. Several roots are joined under an unnamed root.
+     *
+     * The tree is walked without recursion, because lineages in long runs
+     * can be deeper than the stack allows.
+     */
+    #[must_use]
+    pub fn to_newick(&self) -> String {
+        let mut children: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        let mut roots = Vec::new();
+        for (&id, record) in &self.records {
+            match self.tree_parent(record) {
+                Some(parent) => children.entry(parent).or_default().push(id),
+                None => roots.push(id),
+            }
+        }
+
+        let mut newick = String::new();
+        let mut stack = Vec::new();
+        let push_children = |stack: &mut Vec<Visit>, ids: &[u64]| {
+            for (i, &id) in ids.iter().enumerate().rev() {
+                stack.push(Visit::Open(id));
+                if i > 0 {
+                    stack.push(Visit::Comma);
+                }
+            }
+        };
+        if roots.len() > 1 {
+            newick.push('(');
+        }
+        push_children(&mut stack, &roots);
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Open(id) => match children.get(&id) {
+                    Some(ids) => {
+                        newick.push('(');
+                        stack.push(Visit::Close(id));
+                        push_children(&mut stack, ids);
+                    }
+                    None => self.write_newick_label(&mut newick, id),
+                },
+                Visit::Close(id) => {
+                    newick.push(')');
+                    self.write_newick_label(&mut newick, id);
+                }
+                Visit::Comma => newick.push(','),
+            }
+        }
+        if roots.len() > 1 {
+            newick.push(')');
+        }
+        newick.push(';');
+        newick
+    }
+
+    fn write_newick_label(&self, newick: &mut String, id: u64) {
+        let record = &self.records[&id];
+        match self.tree_parent(record) {
+            Some(parent) => {
+                let length = record
+                    .generation
+                    .saturating_sub(self.records[&parent].generation);
+                write!(newick, "{}:{}", id, length).expect("Could not write Newick label.");
+            }
+            None => write!(newick, "{}", id).expect("Could not write Newick label."),
+        }
+    }
+
+    /*
+     * The family tree as a Graphviz DOT graph, with an edge from every known
+     * parent to its child.
+     */
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph genealogy {\n    node [shape=box];\n");
+        for (id, record) in &self.records {
+            writeln!(
+                dot,
+                "    {} [label=\"{}\\n{}\\ngeneration {}\"];",
+                id,
+                id,
+                record.name.replace('\\', "\\\\").replace('"', "\\\""),
+                record.generation,
+            )
+            .expect("Could not write DOT node.");
+        }
+        for (id, record) in &self.records {
+            for parent in record
+                .parents
+                .iter()
+                .filter(|p| self.records.contains_key(p))
+            {
+                writeln!(dot, "    {} -> {};", parent, id).expect("Could not write DOT edge.");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genealogy(records: &[(u64, &[u64], u64)]) -> Genealogy {
+        let mut genealogy = Genealogy::default();
+        for &(id, parents, generation) in records {
+            genealogy.records.insert(
+                id,
+                Record {
+                    name: format!("creature {}", id),
+                    parents: parents.to_vec(),
+                    strain: parents.first().copied().unwrap_or(id),
+                    generation,
+                },
+            );
+        }
+        genealogy
+    }
+
+    #[test]
+    fn newick_hangs_children_from_their_first_parent() {
+        let genealogy = genealogy(&[
+            (0, &[], 1),
+            (1, &[], 1),
+            (2, &[0], 2),
+            (3, &[0, 1], 2),
+            (4, &[2], 4),
+        ]);
+        assert_eq!(genealogy.to_newick(), "(((4:2)2:1,3:1)0,1);");
+    }
+
+    #[test]
+    fn newick_of_a_single_root_has_no_outer_parentheses() {
+        let genealogy = genealogy(&[(5, &[], 1), (6, &[5], 2)]);
+        assert_eq!(genealogy.to_newick(), "(6:1)5;");
+    }
+
+    #[test]
+    fn newick_treats_unknown_parents_as_roots() {
+        let genealogy = genealogy(&[(7, &[3], 2), (8, &[7], 3)]);
+        assert_eq!(genealogy.to_newick(), "(8:1)7;");
+    }
+
+    #[test]
+    fn newick_handles_lineages_deeper_than_the_stack() {
+        let records: Vec<(u64, Vec<u64>, u64)> = (0..100_000)
+            .map(|id| (id, if id == 0 { vec![] } else { vec![id - 1] }, id + 1))
+            .collect();
+        let records: Vec<(u64, &[u64], u64)> = records
+            .iter()
+            .map(|(id, parents, generation)| (*id, parents.as_slice(), *generation))
+            .collect();
+        let newick = genealogy(&records).to_newick();
+        assert!(newick.starts_with("((((("));
+        assert!(newick.ends_with(")0;"));
+    }
+
+    #[test]
+    fn dot_has_an_edge_from_every_known_parent() {
+        let dot = genealogy(&[(0, &[], 1), (1, &[], 1), (2, &[0, 1, 9], 2)]).to_dot();
+        assert!(dot.contains("    0 -> 2;\n"));
+        assert!(dot.contains("    1 -> 2;\n"));
+        assert!(!dot.contains("9 -> 2"));
+    }
+}
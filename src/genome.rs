@@ -1,56 +1,108 @@
 use crate::creature::Action;
+use crate::reproduction::Crossover;
+use crate::traits::{TraitRegistry, TraitSet};
 use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-macro_rules! record_field_names {
-    (
-        pub struct $struct_name:ident {
-            $(pub $key:ident : $type:ty),*
-        }) => {
+/*
+ * Limits on the length of behavior patterns. A mutation that would take a
+ * pattern outside of min_length..=max_length is not applied. Patterns are
+ * at least one instruction long, and a max_length below min_length is read
+ * as min_length.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BehaviorRules {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub initial_length: usize,
+}
 
-        #[derive(Clone, Copy)]
-        pub struct $struct_name {
-            $(pub $key : $type),*
+impl Default for BehaviorRules {
+    fn default() -> Self {
+        Self {
+            min_length: 1,
+            max_length: 16,
+            initial_length: 5,
         }
+    }
+}
 
-        #[derive(Debug)]
-        enum TraitSetType {
-            $(
-                #[allow(non_camel_case_types)]
-                $key
-                ),*
-        }
+impl BehaviorRules {
+    #[must_use]
+    pub fn min(&self) -> usize {
+        self.min_length.max(1)
+    }
 
-        impl $struct_name {
-            #[allow(dead_code)]
-            fn get_fields() -> &'static [&'static str] {
-                static KEYS: &'static [&'static str] = &[$(stringify!($key)),*];
-                KEYS
-            }
+    #[must_use]
+    pub fn max(&self) -> usize {
+        self.max_length.max(self.min())
+    }
+}
 
-            fn get_random_enum() -> &'static TraitSetType {
-                [
-                    $(TraitSetType::$key),*
-                ].choose(&mut rand::thread_rng()).expect("Could not get random enum.")
-            }
+/*
+ * The chance that each mutation operator is applied when a creature
+ * reproduces. Every operator is rolled for independently.
+ *
+ *   trait_rate     - move one point from one trait to another
+ *   point_rate     - replace one instruction with a random one
+ *   swap_rate      - exchange two instructions
+ *   shift_rate     - rotate the pattern one instruction left or right
+ *   insert_rate    - insert a random instruction
+ *   delete_rate    - remove an instruction
+ *   duplicate_rate - repeat a run of instructions after itself
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MutationRates {
+    pub trait_rate: f64,
+    pub point_rate: f64,
+    pub swap_rate: f64,
+    pub shift_rate: f64,
+    pub insert_rate: f64,
+    pub delete_rate: f64,
+    pub duplicate_rate: f64,
+}
+
+impl Default for MutationRates {
+    fn default() -> Self {
+        Self {
+            trait_rate: 0.1,
+            point_rate: 0.1,
+            swap_rate: 0.05,
+            shift_rate: 0.05,
+            insert_rate: 0.05,
+            delete_rate: 0.05,
+            duplicate_rate: 0.02,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+fn roll(rate: f64, rng: &mut impl Rng) -> bool {
+    rng.gen_bool(rate.clamp(0.0, 1.0))
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Behavior {
-    pub action_pattern: [Action; 5],
+    pub action_pattern: Vec<Action>,
 }
 
 impl fmt::Display for Behavior {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut res = String::new();
-        for action in self.action_pattern {
+        for action in &self.action_pattern {
             res += match action {
                 Action::MoveForward => "F",
                 Action::TurnLeft => "L",
                 Action::TurnRight => "R",
                 Action::TurnRandom => "A",
+                Action::IfPlantAhead => "p",
+                Action::IfWallAhead => "w",
+                Action::IfCreatureAhead => "c",
+                Action::IfLifeBelow => "h",
+                Action::Attack => "X",
             }
         }
         write!(f, "{}", res)
@@ -58,51 +110,93 @@ impl fmt::Display for Behavior {
 }
 
 impl Behavior {
-    fn new() -> Self {
-        let actions = Action::iterator().as_slice();
-        let action_pattern: [Action; 5] = [1; 5].map(|_| {
-            *actions
-                .choose(&mut rand::thread_rng())
-                .expect("Could not get random action.")
-        });
+    fn new(rules: &BehaviorRules, rng: &mut impl Rng) -> Self {
+        let length = rules.initial_length.clamp(rules.min(), rules.max());
+        let action_pattern = (0..length).map(|_| random_action(rng)).collect();
         Self { action_pattern }
     }
-}
 
-#[derive(Clone, Copy)]
-pub struct Trait {
-    pub value: i32,
-    weight: f64,
-}
+    fn insert(&mut self, rules: &BehaviorRules, rng: &mut impl Rng) {
+        if self.action_pattern.len() < rules.max() {
+            let i = rng.gen_range(0..=self.action_pattern.len());
+            self.action_pattern.insert(i, random_action(rng));
+        }
+    }
 
-impl Trait {
-    fn new(value: i32, weight: f64) -> Self {
-        Self { value, weight }
+    fn delete(&mut self, rules: &BehaviorRules, rng: &mut impl Rng) {
+        if self.action_pattern.len() > rules.min() {
+            let i = rng.gen_range(0..self.action_pattern.len());
+            self.action_pattern.remove(i);
+        }
     }
 
-    #[must_use]
-    pub fn get_value(&self) -> f64 {
-        f64::from(self.value) * self.weight
+    /*
+     * Copies a random run of instructions and inserts the copy right after
+     * the original, as long as the result fits within max_length.
+     */
+    fn duplicate(&mut self, rules: &BehaviorRules, rng: &mut impl Rng) {
+        let length = self.action_pattern.len();
+        if length >= rules.max() {
+            return;
+        }
+        let start = rng.gen_range(0..length);
+        let run = rng.gen_range(1..=(length - start).min(rules.max() - length));
+        let copy = self.action_pattern[start..start + run].to_vec();
+        self.action_pattern.splice(start + run..start + run, copy);
     }
 
-    pub fn set_value(&mut self, value: i32) -> bool {
-        if !(1..=10).contains(&value) {
-            return false;
+    fn point(&mut self, rng: &mut impl Rng) {
+        let i = rng.gen_range(0..self.action_pattern.len());
+        self.action_pattern[i] = random_action(rng);
+    }
+
+    fn swap(&mut self, rng: &mut impl Rng) {
+        let length = self.action_pattern.len();
+        self.action_pattern
+            .swap(rng.gen_range(0..length), rng.gen_range(0..length));
+    }
+
+    fn shift(&mut self, rng: &mut impl Rng) {
+        if rng.gen_bool(0.5) {
+            self.action_pattern.rotate_left(1);
+        } else {
+            self.action_pattern.rotate_right(1);
         }
+    }
 
-        self.value = value;
-        true
+    pub fn mutate(&mut self, rules: &BehaviorRules, rates: &MutationRates, rng: &mut impl Rng) {
+        if self.action_pattern.is_empty() {
+            return;
+        }
+        if roll(rates.point_rate, rng) {
+            self.point(rng);
+        }
+        if roll(rates.swap_rate, rng) {
+            self.swap(rng);
+        }
+        if roll(rates.shift_rate, rng) {
+            self.shift(rng);
+        }
+        if roll(rates.insert_rate, rng) {
+            self.insert(rules, rng);
+        }
+        if roll(rates.delete_rate, rng) {
+            self.delete(rules, rng);
+        }
+        if roll(rates.duplicate_rate, rng) {
+            self.duplicate(rules, rng);
+        }
     }
 }
 
-record_field_names! {
-pub struct TraitSet {
-    pub aging_speed_divisor: Trait,
-    pub eating_efficiency: Trait
-}
+fn random_action(rng: &mut impl Rng) -> Action {
+    *Action::iterator()
+        .as_slice()
+        .choose(rng)
+        .expect("Could not get random action.")
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Genome {
     pub trait_set: TraitSet,
     pub behavior: Behavior,
@@ -116,39 +210,68 @@ impl fmt::Display for Genome {
 
 impl Genome {
     #[must_use]
-    pub fn new_even_distribution() -> Self {
+    pub fn new(trait_set: TraitSet, rules: &BehaviorRules, rng: &mut impl Rng) -> Self {
         Self {
-            trait_set: TraitSet {
-                aging_speed_divisor: Trait::new(5, 0.2),
-                eating_efficiency: Trait::new(5, 50.0),
-            },
-            behavior: Behavior::new(),
+            trait_set,
+            behavior: Behavior::new(rules, rng),
         }
     }
 
-    fn trait_modify(&mut self, t: &TraitSetType, n: i32) -> bool {
-        match t {
-            TraitSetType::aging_speed_divisor => self
-                .trait_set
-                .aging_speed_divisor
-                .set_value(self.trait_set.aging_speed_divisor.value + n),
-            TraitSetType::eating_efficiency => self
-                .trait_set
-                .eating_efficiency
-                .set_value(self.trait_set.eating_efficiency.value + n),
+    /*
+     * Recombines the trait values and the behavior patterns of two parents.
+     * The behavior rules are applied afterwards, so a child of two long
+     * patterns is cut back to max_length.
+     */
+    #[must_use]
+    pub fn crossover(
+        a: &Self,
+        b: &Self,
+        crossover: Crossover,
+        rules: &BehaviorRules,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let trait_set = TraitSet {
+            values: crossover.apply(&a.trait_set.values, &b.trait_set.values, rng),
+        };
+
+        let mut action_pattern =
+            crossover.apply(&a.behavior.action_pattern, &b.behavior.action_pattern, rng);
+        action_pattern.truncate(rules.max());
+        Self {
+            trait_set,
+            behavior: Behavior { action_pattern },
         }
     }
 
     /*
-     * This function only commits changes if both modifications succeed.
+     * Moves one point from trait a to trait b, so the sum of all trait
+     * values stays the same. Nothing changes if either value would leave
+     * its range.
      */
-    fn trait_modify_duo(&mut self, a: &TraitSetType, b: &TraitSetType) {
-        if self.trait_modify(a, -1) && !self.trait_modify(b, 1) {
-            self.trait_modify(a, 1);
+    fn trait_modify_duo(&mut self, registry: &TraitRegistry, a: usize, b: usize) {
+        let traits = registry.traits();
+        let values = &mut self.trait_set.values;
+        if a != b && traits[a].contains(values[a] - 1) && traits[b].contains(values[b] + 1) {
+            values[a] -= 1;
+            values[b] += 1;
         }
     }
 
-    pub fn mutate(&mut self) {
-        self.trait_modify_duo(TraitSet::get_random_enum(), TraitSet::get_random_enum());
+    pub fn mutate(
+        &mut self,
+        registry: &TraitRegistry,
+        rules: &BehaviorRules,
+        rates: &MutationRates,
+        rng: &mut impl Rng,
+    ) {
+        self.trait_set.conform(registry);
+        if !registry.is_empty() && roll(rates.trait_rate, rng) {
+            self.trait_modify_duo(
+                registry,
+                rng.gen_range(0..registry.len()),
+                rng.gen_range(0..registry.len()),
+            );
+        }
+        self.behavior.mutate(rules, rates, rng);
     }
 }
@@ -0,0 +1,228 @@
+use crate::creature::{Creature, Direction, LifetimeStats};
+use crate::position::Position;
+use crate::world::WorldState;
+use serde::{Deserialize, Serialize};
+
+pub const KEYFRAME_INTERVAL: usize = 100;
+
+/*
+ * The fields of one creature that changed between two consecutive frames.
+ * Everything else about a creature is fixed for its whole life.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+struct CreatureDelta {
+    index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    life: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    direction: Option<Direction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<Position>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    program_counter: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<LifetimeStats>,
+}
+
+impl CreatureDelta {
+    fn between(index: usize, old: &Creature, new: &Creature) -> Option<Self> {
+        let delta = Self {
+            index,
+            life: (old.life.to_bits() != new.life.to_bits()).then_some(new.life),
+            direction: (old.direction != new.direction).then_some(new.direction),
+            position: (old.position != new.position).then_some(new.position),
+            program_counter: (old.program_counter != new.program_counter)
+                .then_some(new.program_counter),
+            stats: (old.stats != new.stats).then_some(new.stats),
+        };
+        if delta.life.is_none()
+            && delta.direction.is_none()
+            && delta.position.is_none()
+            && delta.program_counter.is_none()
+            && delta.stats.is_none()
+        {
+            return None;
+        }
+        Some(delta)
+    }
+
+    fn apply(&self, creature: &mut Creature) {
+        if let Some(life) = self.life {
+            creature.life = life;
+        }
+        if let Some(direction) = self.direction {
+            creature.direction = direction;
+        }
+        if let Some(position) = self.position {
+            creature.position = position;
+        }
+        if let Some(program_counter) = self.program_counter {
+            creature.program_counter = program_counter;
+        }
+        if let Some(stats) = self.stats {
+            creature.stats = stats;
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Delta {
+    tick: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    changed: Vec<CreatureDelta>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    added: Vec<Creature>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    plants: Vec<(usize, u64)>,
+}
+
+impl Delta {
+    fn between(old: &WorldState, new: &WorldState) -> Self {
+        let mut delta = Self {
+            tick: new.tick,
+            ..Self::default()
+        };
+        for (index, creature) in new.creatures.iter().enumerate() {
+            match old.creatures.get(index) {
+                Some(old_creature) => {
+                    if let Some(change) = CreatureDelta::between(index, old_creature, creature) {
+                        delta.changed.push(change);
+                    }
+                }
+                None => delta.added.push(creature.clone()),
+            }
+        }
+        let old_plants = old.plants.regrows_at();
+        for (index, &regrows_at) in new.plants.regrows_at().iter().enumerate() {
+            if old_plants[index] != regrows_at {
+                delta.plants.push((index, regrows_at));
+            }
+        }
+        delta
+    }
+
+    fn apply(&self, state: &mut WorldState) {
+        state.tick = self.tick;
+        for change in &self.changed {
+            change.apply(&mut state.creatures[change.index]);
+        }
+        state.creatures.extend(self.added.iter().cloned());
+        for &(index, regrows_at) in &self.plants {
+            state.plants.set_regrows_at(index, regrows_at);
+        }
+    }
+}
+
+/*
+ * The frames of a recorded world. Every KEYFRAME_INTERVAL-th frame is stored
+ * in full and the frames in between are stored as the changes since the
+ * previous frame, so any frame can be rebuilt from its nearest keyframe.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct History {
+    keyframe_interval: usize,
+    keyframes: Vec<WorldState>,
+    deltas: Vec<Delta>,
+    #[serde(skip)]
+    last: Option<WorldState>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(KEYFRAME_INTERVAL)
+    }
+}
+
+impl History {
+    #[must_use]
+    pub fn new(keyframe_interval: usize) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            keyframes: Vec::new(),
+            deltas: Vec::new(),
+            last: None,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    pub fn push(&mut self, state: &WorldState) {
+        if self.len() % self.keyframe_interval == 0 {
+            self.keyframes.push(state.clone());
+            self.deltas.push(Delta::default());
+        } else {
+            if self.last.is_none() {
+                self.last = Some(self.frame(self.len() - 1));
+            }
+            let last = self.last.as_ref().expect("Could not get last frame.");
+            self.deltas.push(Delta::between(last, state));
+        }
+        self.last = Some(state.clone());
+    }
+
+    #[must_use]
+    pub fn frame(&self, index: usize) -> WorldState {
+        let keyframe = index / self.keyframe_interval;
+        let mut state = self.keyframes[keyframe].clone();
+        for delta in &self.deltas[keyframe * self.keyframe_interval + 1..=index] {
+            delta.apply(&mut state);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reproduction::ContinuousReproduction;
+    use crate::world::{World, WorldConfig};
+
+    fn to_json(state: &WorldState) -> String {
+        serde_json::to_string(state).expect("Could not serialize state.")
+    }
+
+    /*
+     * Continuous reproduction makes creatures split in the first step, so
+     * the deltas cover added creatures as well as changed ones and eaten
+     * plants.
+     */
+    #[test]
+    fn frames_match_pushed_states_across_keyframes() {
+        let config = WorldConfig {
+            continuous: ContinuousReproduction {
+                enabled: true,
+                threshold: 150.0,
+                ..ContinuousReproduction::default()
+            },
+            ..WorldConfig::default()
+        };
+        let mut world = World::new(config, false, 7);
+        for _ in 0..20 {
+            world.add_creature("test");
+        }
+
+        let mut history = History::new(4);
+        let mut states = Vec::new();
+        for _ in 0..10 {
+            history.push(&world.current_state);
+            states.push(to_json(&world.current_state));
+            world.step();
+        }
+
+        let json = serde_json::to_string(&history).expect("Could not serialize history.");
+        let loaded: History = serde_json::from_str(&json).expect("Could not load history.");
+        assert_eq!(history.len(), states.len());
+        for (i, state) in states.iter().enumerate() {
+            assert_eq!(&to_json(&history.frame(i)), state, "frame {}", i);
+            assert_eq!(&to_json(&loaded.frame(i)), state, "loaded frame {}", i);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::terrain::Cell;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+
+/*
+ * A hand-drawn world: the terrain of every cell and whether a plant grows
+ * there. Row 0 of the file or image is row 0 of the world.
+ *
+ * In text files '#' is a wall, '~' is water, ',' is rough ground, '.' is
+ * open ground with a plant and anything else is open ground.
+ *
+ * In PNG and PPM images each pixel is mapped to the closest of these
+ * colours: black is a wall, blue is water, brown is rough ground, green is
+ * open ground with a plant and white is open ground.
+ */
+pub struct Level {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Cell>,
+    pub fertile: Vec<bool>,
+}
+
+const PALETTE: [((u8, u8, u8), Cell, bool); 5] = [
+    ((0, 0, 0), Cell::Wall, false),
+    ((0, 0, 255), Cell::Water, false),
+    ((128, 64, 0), Cell::Rough, false),
+    ((0, 255, 0), Cell::Open, true),
+    ((255, 255, 255), Cell::Open, false),
+];
+
+impl Level {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("png") => Self::from_png(path),
+            Some("ppm") => Self::from_ppm(&fs::read(path)?),
+            _ => Ok(Self::from_text(&fs::read_to_string(path)?)),
+        }
+    }
+
+    #[must_use]
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = lines.len();
+        let mut level = Self::empty(width, height);
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let i = y * width + x;
+                match c {
+                    '#' => level.cells[i] = Cell::Wall,
+                    '~' => level.cells[i] = Cell::Water,
+                    ',' => level.cells[i] = Cell::Rough,
+                    '.' => level.fertile[i] = true,
+                    _ => {}
+                }
+            }
+        }
+        level
+    }
+
+    fn from_png(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut decoder = png::Decoder::new(File::open(path)?);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks(channels)
+            .map(|p| {
+                if channels < 3 {
+                    (p[0], p[0], p[0])
+                } else {
+                    (p[0], p[1], p[2])
+                }
+            })
+            .collect();
+        Ok(Self::from_pixels(
+            info.width as usize,
+            info.height as usize,
+            pixels,
+        ))
+    }
+
+    /*
+     * Reads both the plain (P3) and the binary (P6) variant.
+     */
+    fn from_ppm(data: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut position = 0;
+        let mut next_token = || -> Result<String, Box<dyn Error>> {
+            let mut token = String::new();
+            while position < data.len() {
+                let c = data[position] as char;
+                if c == '#' && token.is_empty() {
+                    while position < data.len() && data[position] != b'\n' {
+                        position += 1;
+                    }
+                } else if c.is_ascii_whitespace() {
+                    position += 1;
+                    if !token.is_empty() {
+                        return Ok(token);
+                    }
+                    continue;
+                } else {
+                    token.push(c);
+                }
+                position += 1;
+            }
+            if token.is_empty() {
+                return Err("Unexpected end of PPM data.".into());
+            }
+            Ok(token)
+        };
+
+        let magic = next_token()?;
+        let width: usize = next_token()?.parse()?;
+        let height: usize = next_token()?.parse()?;
+        let max_value: u32 = next_token()?.parse()?;
+        let scale = |v: u32| (v * 255 / max_value.max(1)) as u8;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        match magic.as_str() {
+            "P3" => {
+                for _ in 0..width * height {
+                    let r = scale(next_token()?.parse()?);
+                    let g = scale(next_token()?.parse()?);
+                    let b = scale(next_token()?.parse()?);
+                    pixels.push((r, g, b));
+                }
+            }
+            "P6" => {
+                let bytes_per_sample = if max_value > 255 { 2 } else { 1 };
+                let start = position;
+                let needed = width * height * 3 * bytes_per_sample;
+                let raster = data
+                    .get(start..start + needed)
+                    .ok_or("Unexpected end of PPM data.")?;
+                let sample = |i: usize| {
+                    if bytes_per_sample == 2 {
+                        scale(u32::from(raster[2 * i]) << 8 | u32::from(raster[2 * i + 1]))
+                    } else {
+                        scale(u32::from(raster[i]))
+                    }
+                };
+                for i in 0..width * height {
+                    pixels.push((sample(3 * i), sample(3 * i + 1), sample(3 * i + 2)));
+                }
+            }
+            _ => return Err(format!("Unsupported PPM format {}.", magic).into()),
+        }
+        Ok(Self::from_pixels(width, height, pixels))
+    }
+
+    fn from_pixels(width: usize, height: usize, pixels: Vec<(u8, u8, u8)>) -> Self {
+        let mut level = Self::empty(width, height);
+        for (i, (r, g, b)) in pixels.into_iter().enumerate().take(width * height) {
+            let distance = |(pr, pg, pb): (u8, u8, u8)| {
+                let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+                d(r, pr) + d(g, pg) + d(b, pb)
+            };
+            let (_, cell, fertile) = PALETTE
+                .iter()
+                .min_by_key(|(colour, _, _)| distance(*colour))
+                .expect("Could not match pixel colour.");
+            level.cells[i] = *cell;
+            level.fertile[i] = *fertile;
+        }
+        level
+    }
+
+    fn empty(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::Open; width * height],
+            fertile: vec![false; width * height],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+     * One pixel of each palette colour, slightly off so that the closest
+     * colour has to be picked.
+     */
+    const PIXELS: [u8; 15] = [10, 0, 0, 0, 0, 240, 120, 70, 0, 0, 250, 10, 255, 250, 255];
+
+    fn assert_palette_row(level: &Level) {
+        assert_eq!((level.width, level.height), (5, 1));
+        assert!(level.cells == [Cell::Wall, Cell::Water, Cell::Rough, Cell::Open, Cell::Open]);
+        assert_eq!(level.fertile, [false, false, false, true, false]);
+    }
+
+    #[test]
+    fn reads_plain_ppm_with_comments() {
+        let samples: Vec<String> = PIXELS.iter().map(u8::to_string).collect();
+        let data = format!("P3\n# a comment\n5 1\n255\n{}\n", samples.join(" "));
+        assert_palette_row(&Level::from_ppm(data.as_bytes()).expect("Could not read P3."));
+    }
+
+    #[test]
+    fn reads_binary_ppm() {
+        let mut data = b"P6\n5 1\n255\n".to_vec();
+        data.extend_from_slice(&PIXELS);
+        assert_palette_row(&Level::from_ppm(&data).expect("Could not read P6."));
+    }
+
+    #[test]
+    fn rejects_truncated_binary_ppm() {
+        let mut data = b"P6\n5 1\n255\n".to_vec();
+        data.extend_from_slice(&PIXELS[..14]);
+        assert!(Level::from_ppm(&data).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+pub mod creature;
+pub mod fitness;
+pub mod genealogy;
+pub mod genome;
+pub mod history;
+pub mod level;
+pub mod map;
+pub mod movement;
+pub mod plant;
+pub mod position;
+pub mod predation;
+pub mod reproduction;
+pub mod scenario;
+pub mod selection;
+pub mod simulation;
+pub mod species;
+pub mod stats;
+pub mod terminal_graphics;
+pub mod terrain;
+pub mod topology;
+pub mod traits;
+pub mod world;
+
+pub use scenario::Scenario;
+pub use simulation::Simulation;
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DisplayMode {
+    #[value(name = "static")]
+    TerminalStatic,
+    #[value(name = "dynamic")]
+    TerminalDynamic,
+}
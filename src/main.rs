@@ -1,61 +1,148 @@
-pub mod creature;
-pub mod genome;
-pub mod map;
-pub mod position;
-pub mod terminal_graphics;
-pub mod world;
+mod cli;
+
+use clap::Parser;
+use cli::{Cli, Command, SimulationArgs};
+use creature_simulation::stats::GenerationStats;
+use creature_simulation::world::World;
+use creature_simulation::Simulation;
+use log::info;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
 // Log types are error, warn, info, debug, and trace.
 
-use log::info;
-use rand::seq::SliceRandom;
+fn evolve(args: &SimulationArgs, output: Option<&str>, record_last: bool) -> Simulation {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    info!("Simulation has started with seed {}.", seed);
 
-#[derive(PartialEq, Eq)]
-pub enum DisplayMode {
-    TerminalStatic,
-    TerminalDynamic,
+    let mut simulation = Simulation::new(args.scenario(), seed);
+    let traits = &simulation.scenario().world.traits;
+    let mut writer = output.map(|path| {
+        let file = File::create(path).unwrap_or_else(|_| panic!("Could not create {}.", path));
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", GenerationStats::csv_header(traits))
+            .expect("Could not write CSV header.");
+        writer
+    });
+
+    let generations = simulation.scenario().schedule.generations;
+    for generation in 0..generations {
+        let save_history = if args.record_generations.is_empty() {
+            record_last && args.record.is_some() && generation + 1 == generations
+        } else {
+            args.record_generations.contains(&generation)
+        };
+        let stats = simulation.run_generation(save_history);
+        record_generation(args, &simulation, generation);
+        if let Some(writer) = &mut writer {
+            writeln!(writer, "{}", stats.csv_row()).expect("Could not write CSV row.");
+        }
+    }
+    simulation
 }
 
-fn main() {
-    log4rs::init_file("src/log4rs.yaml", Default::default())
-        .expect("Could not open src/log4rs.yaml.");
-
-    info!("Simulation has started.");
-
-    let data = String::from_utf8_lossy(include_bytes!("../resources/names.in"));
-    let names: Vec<&str> = data.split('\n').collect();
-    let carrying_capacity = 100;
-    let generations = 100;
-    let simulation_steps = 1000;
-
-    let mut world1 = world::World::new(80, 30, "World".to_string(), carrying_capacity, false);
-    for _ in 0..carrying_capacity {
-        world1.add_creature(
-            names
-                .choose(&mut rand::thread_rng())
-                .expect("Could not add creature."),
-        );
+/*
+ * Writes the replay of a generation listed in --record-generations to the
+ * --record path with the generation number added before the extension.
+ */
+fn record_generation(args: &SimulationArgs, simulation: &Simulation, generation: usize) {
+    let Some(record) = &args.record else {
+        return;
+    };
+    if !args.record_generations.contains(&generation) {
+        return;
     }
-    for generation in 0..generations {
-        let mut world2 = world::World::new(80, 30, "World".to_string(), carrying_capacity, false);
-        world2.add_creatures_from_world(world1);
-        world2.simulate(simulation_steps);
-        world1 = world2;
-
-        info!(
-            "{} creatures survived generation {} ({}%).",
-            world1.current_state.num_alive(),
-            generation,
-            100 * world1.current_state.num_alive() / carrying_capacity,
-        );
+    let record = Path::new(record);
+    let stem = record.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match record.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, generation, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, generation),
+    };
+    let path = record.with_file_name(name);
+    let path = path.to_string_lossy();
+    simulation
+        .world()
+        .save_replay(&path)
+        .unwrap_or_else(|e| panic!("Could not save replay {}: {}", path, e));
+    info!("Saved replay of generation {} to {}.", generation, path);
+}
+
+fn save(args: &SimulationArgs, simulation: &Simulation) {
+    if let Some(path) = &args.save {
+        simulation
+            .world()
+            .save(path)
+            .unwrap_or_else(|e| panic!("Could not save snapshot {}: {}", path, e));
+        info!("Saved snapshot to {}.", path);
     }
+    if let Some(path) = args
+        .record
+        .as_ref()
+        .filter(|_| args.record_generations.is_empty())
+    {
+        simulation
+            .world()
+            .save_replay(path)
+            .unwrap_or_else(|e| panic!("Could not save replay {}: {}", path, e));
+        info!("Saved replay to {}.", path);
+    }
+    if let Some(path) = &args.newick {
+        fs::write(path, simulation.genealogy().to_newick())
+            .unwrap_or_else(|e| panic!("Could not save Newick tree {}: {}", path, e));
+        info!("Saved Newick tree to {}.", path);
+    }
+    if let Some(path) = &args.dot {
+        fs::write(path, simulation.genealogy().to_dot())
+            .unwrap_or_else(|e| panic!("Could not save DOT graph {}: {}", path, e));
+        info!("Saved DOT graph to {}.", path);
+    }
+}
 
-    let mut world2 = world::World::new(80, 30, "World".to_string(), carrying_capacity, true);
-    world2.add_creatures_from_world(world1);
-    world2.simulate(simulation_steps);
-    world1 = world2;
+fn main() {
+    let cli = Cli::parse();
 
-    info!("Simulation has Ended.");
+    log4rs::init_file(&cli.log_config, Default::default())
+        .unwrap_or_else(|_| panic!("Could not open {}.", cli.log_config));
 
-    world1.display_map(DisplayMode::TerminalDynamic, &world1.history[0..], 10);
+    match cli.command {
+        Command::Run {
+            simulation: args,
+            display,
+            frame_delay,
+        } => {
+            let mut simulation = evolve(&args, None, false);
+            let generation = simulation.generation();
+            simulation.run_generation(true);
+            record_generation(&args, &simulation, generation);
+            save(&args, &simulation);
+
+            info!("Simulation has Ended.");
+
+            let world = simulation.world();
+            world.display_map(display, &world.history, frame_delay);
+        }
+        Command::Replay {
+            file,
+            display,
+            frame_delay,
+        } => {
+            let world = World::load_replay(&file)
+                .unwrap_or_else(|e| panic!("Could not load replay {}: {}", file, e));
+            if world.history.is_empty() {
+                panic!("Replay {} contains no frames.", file);
+            }
+            world.display_map(display, &world.history, frame_delay);
+        }
+        Command::Headless {
+            simulation: args,
+            output,
+        } => {
+            let simulation = evolve(&args, output.as_deref(), true);
+            save(&args, &simulation);
+
+            info!("Simulation has Ended.");
+        }
+    }
 }
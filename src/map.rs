@@ -1,5 +1,7 @@
 use crate::creature::Direction;
 use crate::position::Position;
+use crate::predation::Diet;
+use crate::terrain::Cell;
 use colored::Colorize;
 
 pub struct Map {
@@ -20,23 +22,30 @@ impl Map {
         }
     }
 
-    pub fn set_creature(&mut self, position: Position, direction: Direction, life: i32) {
+    pub fn set_creature(
+        &mut self,
+        position: Position,
+        direction: Direction,
+        diet: Diet,
+        life: i32,
+    ) {
         if life == 0 {
             self.set_slot(position, 'x', 1);
             return;
         }
-        match direction {
-            Direction::North => self.set_slot(position, '^', life),
-            Direction::South => self.set_slot(position, 'v', life),
-            Direction::East => self.set_slot(position, '>', life),
-            Direction::West => self.set_slot(position, '<', life),
-        }
+        self.set_slot(position, diet.glyph(direction), life);
     }
 
     pub fn set_plant(&mut self, position: Position) {
         self.set_slot(position, '.', 2);
     }
 
+    pub fn set_terrain(&mut self, position: Position, cell: Cell) {
+        if cell != Cell::Open {
+            self.set_slot(position, cell.glyph(), 1);
+        }
+    }
+
     fn get_slot(&mut self, position: Position) -> (char, i32) {
         if position.x >= 0
             && position.x < self.width as i32
@@ -76,6 +85,18 @@ impl Map {
                 } else if y.0 == 'c' {
                     let s = format!("{}", y.0).yellow();
                     print!("{}", s);
+                } else if y.0 == '~' {
+                    let s = format!("{}", y.0).blue();
+                    print!("{}", s);
+                } else if y.0 == ',' {
+                    let s = format!("{}", y.0).yellow();
+                    print!("{}", s);
+                } else if y.0 == '#' {
+                    let s = format!("{}", y.0).white();
+                    print!("{}", s);
+                } else if Diet::is_carnivore_glyph(y.0) {
+                    let s = format!("{}", y.0).red();
+                    print!("{}", s);
                 } else {
                     print!("{}", y.0);
                 }
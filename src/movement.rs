@@ -0,0 +1,129 @@
+use crate::creature::Direction;
+use crate::position::Position;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/*
+ * The grid geometry creatures move on. On the hex grid odd rows are shifted
+ * half a cell east, so the neighbours of a cell depend on its row.
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementModel {
+    #[default]
+    FourNeighbour,
+    EightNeighbour,
+    Hex,
+}
+
+impl MovementModel {
+    /*
+     * The directions a creature can face, in clockwise order.
+     */
+    #[must_use]
+    pub fn directions(self) -> &'static [Direction] {
+        match self {
+            Self::FourNeighbour => &[
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::West,
+            ],
+            Self::EightNeighbour => &[
+                Direction::North,
+                Direction::NorthEast,
+                Direction::East,
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::West,
+                Direction::NorthWest,
+            ],
+            Self::Hex => &[
+                Direction::NorthEast,
+                Direction::East,
+                Direction::SouthEast,
+                Direction::SouthWest,
+                Direction::West,
+                Direction::NorthWest,
+            ],
+        }
+    }
+
+    pub fn random_direction(self, rng: &mut impl Rng) -> Direction {
+        *self
+            .directions()
+            .choose(rng)
+            .expect("Could not select random direction.")
+    }
+
+    fn rotate(self, direction: Direction, steps: usize) -> Direction {
+        let directions = self.directions();
+        let index = directions.iter().position(|d| *d == direction).unwrap_or(0);
+        directions[(index + steps) % directions.len()]
+    }
+
+    #[must_use]
+    pub fn turn_right(self, direction: Direction) -> Direction {
+        self.rotate(direction, 1)
+    }
+
+    #[must_use]
+    pub fn turn_left(self, direction: Direction) -> Direction {
+        self.rotate(direction, self.directions().len() - 1)
+    }
+
+    #[must_use]
+    pub fn turn_around(self, direction: Direction) -> Direction {
+        self.rotate(direction, self.directions().len() / 2)
+    }
+
+    /*
+     * The step from the position to its neighbour in the given direction.
+     */
+    #[must_use]
+    pub fn offset(self, position: Position, direction: Direction) -> (i32, i32) {
+        let odd_row = position.y.rem_euclid(2) == 1;
+        match (self, direction) {
+            (Self::Hex, Direction::NorthEast) => (i32::from(odd_row), 1),
+            (Self::Hex, Direction::NorthWest) => (i32::from(odd_row) - 1, 1),
+            (Self::Hex, Direction::SouthEast) => (i32::from(odd_row), -1),
+            (Self::Hex, Direction::SouthWest) => (i32::from(odd_row) - 1, -1),
+            (_, Direction::North) => (0, 1),
+            (_, Direction::NorthEast) => (1, 1),
+            (_, Direction::East) => (1, 0),
+            (_, Direction::SouthEast) => (1, -1),
+            (_, Direction::South) => (0, -1),
+            (_, Direction::SouthWest) => (-1, -1),
+            (_, Direction::West) => (-1, 0),
+            (_, Direction::NorthWest) => (-1, 1),
+        }
+    }
+
+    /*
+     * The number of moves between two positions: the Manhattan distance on
+     * the four-neighbour grid, the Chebyshev distance on the eight-neighbour
+     * grid and the cube distance on the hex grid.
+     */
+    #[must_use]
+    pub fn distance(self, a: Position, b: Position) -> f64 {
+        let diff = b - a;
+        match self {
+            Self::FourNeighbour => f64::from(diff.x.abs() + diff.y.abs()),
+            Self::EightNeighbour => f64::from(diff.x.abs().max(diff.y.abs())),
+            Self::Hex => {
+                let cube = |p: Position| {
+                    let q = p.x - (p.y - p.y.rem_euclid(2)) / 2;
+                    (q, p.y, -q - p.y)
+                };
+                let (a, b) = (cube(a), cube(b));
+                let d = (a.0 - b.0)
+                    .abs()
+                    .max((a.1 - b.1).abs())
+                    .max((a.2 - b.2).abs());
+                f64::from(d)
+            }
+        }
+    }
+}
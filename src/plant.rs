@@ -0,0 +1,354 @@
+use crate::position::Position;
+use crate::terrain::Terrain;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+/*
+ * Decides where plants can grow. The density is the probability, from 0 to 1,
+ * that the cell at a position is fertile.
+ */
+pub trait PlantLayout {
+    fn density(&self, position: Position, width: usize, height: usize) -> f64;
+}
+
+/*
+ * SplitMix64, a fixed hash, so that the same seed gives the same plants on
+ * every platform and with every Rust release, unlike the std hashers.
+ */
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn hash_to_unit(x: i64, y: i64, seed: u64) -> f64 {
+    let hash = splitmix64(splitmix64(splitmix64(seed) ^ x as u64) ^ y as u64);
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+fn world_center(width: usize, height: usize) -> Position {
+    Position::new(width as i32 / 2, height as i32 / 2)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UniformLayout {
+    pub density: f64,
+    pub barren_radius: f64,
+}
+
+impl Default for UniformLayout {
+    fn default() -> Self {
+        Self {
+            density: 0.25,
+            barren_radius: 6.0,
+        }
+    }
+}
+
+impl PlantLayout for UniformLayout {
+    fn density(&self, position: Position, width: usize, height: usize) -> f64 {
+        if position.dist(&world_center(width, height)) <= self.barren_radius {
+            return 0.0;
+        }
+        self.density
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RadialGradientLayout {
+    pub center: Option<Position>,
+    pub radius: Option<f64>,
+    pub inner_density: f64,
+    pub outer_density: f64,
+}
+
+impl Default for RadialGradientLayout {
+    fn default() -> Self {
+        Self {
+            center: None,
+            radius: None,
+            inner_density: 0.6,
+            outer_density: 0.0,
+        }
+    }
+}
+
+impl PlantLayout for RadialGradientLayout {
+    fn density(&self, position: Position, width: usize, height: usize) -> f64 {
+        let center = self.center.unwrap_or_else(|| world_center(width, height));
+        let radius = self
+            .radius
+            .unwrap_or_else(|| center.dist(&Position::new(0, 0)).max(1.0));
+        let t = (position.dist(&center) / radius).min(1.0);
+        self.inner_density + (self.outer_density - self.inner_density) * t
+    }
+}
+
+/*
+ * Smooth value noise: random values on a lattice with the given spacing,
+ * interpolated in between. Cells where the noise exceeds the threshold form
+ * the patches.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PatchesLayout {
+    pub scale: f64,
+    pub threshold: f64,
+    pub density: f64,
+    pub seed: u64,
+}
+
+impl Default for PatchesLayout {
+    fn default() -> Self {
+        Self {
+            scale: 8.0,
+            threshold: 0.5,
+            density: 0.6,
+            seed: 0,
+        }
+    }
+}
+
+impl PatchesLayout {
+    fn noise(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+        let corner = |dx: f64, dy: f64| hash_to_unit((x0 + dx) as i64, (y0 + dy) as i64, self.seed);
+        let top = corner(0.0, 0.0) + (corner(1.0, 0.0) - corner(0.0, 0.0)) * tx;
+        let bottom = corner(0.0, 1.0) + (corner(1.0, 1.0) - corner(0.0, 1.0)) * tx;
+        top + (bottom - top) * ty
+    }
+}
+
+impl PlantLayout for PatchesLayout {
+    fn density(&self, position: Position, _width: usize, _height: usize) -> f64 {
+        let scale = self.scale.max(1.0);
+        if self.noise(f64::from(position.x) / scale, f64::from(position.y) / scale) > self.threshold
+        {
+            return self.density;
+        }
+        0.0
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StripesLayout {
+    pub width: usize,
+    pub gap: usize,
+    pub vertical: bool,
+    pub density: f64,
+}
+
+impl Default for StripesLayout {
+    fn default() -> Self {
+        Self {
+            width: 3,
+            gap: 5,
+            vertical: true,
+            density: 0.6,
+        }
+    }
+}
+
+impl PlantLayout for StripesLayout {
+    fn density(&self, position: Position, _width: usize, _height: usize) -> f64 {
+        let coordinate = if self.vertical {
+            position.x
+        } else {
+            position.y
+        };
+        let period = (self.width + self.gap).max(1) as i32;
+        if (coordinate.rem_euclid(period) as usize) < self.width {
+            return self.density;
+        }
+        0.0
+    }
+}
+
+/*
+ * A layout read from a text file, one line per row. Cells marked with '.'
+ * are fertile; every other character and everything beyond the end of the
+ * file is barren.
+ */
+pub struct GridLayout {
+    rows: Vec<Vec<bool>>,
+}
+
+impl GridLayout {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read_to_string(path)?;
+        let rows = data
+            .lines()
+            .map(|line| line.chars().map(|c| c == '.').collect())
+            .collect();
+        Ok(Self { rows })
+    }
+}
+
+impl PlantLayout for GridLayout {
+    fn density(&self, position: Position, _width: usize, _height: usize) -> f64 {
+        let fertile = usize::try_from(position.y)
+            .ok()
+            .and_then(|y| self.rows.get(y))
+            .zip(usize::try_from(position.x).ok())
+            .and_then(|(row, x)| row.get(x))
+            .copied()
+            .unwrap_or(false);
+        if fertile {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PlantLayoutConfig {
+    Uniform(UniformLayout),
+    RadialGradient(RadialGradientLayout),
+    Patches(PatchesLayout),
+    Stripes(StripesLayout),
+    File { path: String },
+}
+
+impl Default for PlantLayoutConfig {
+    fn default() -> Self {
+        Self::Uniform(UniformLayout::default())
+    }
+}
+
+impl PlantLayoutConfig {
+    #[must_use]
+    pub fn build(&self) -> Box<dyn PlantLayout> {
+        match self {
+            Self::Uniform(layout) => Box::new(layout.clone()),
+            Self::RadialGradient(layout) => Box::new(layout.clone()),
+            Self::Patches(layout) => Box::new(layout.clone()),
+            Self::Stripes(layout) => Box::new(layout.clone()),
+            Self::File { path } => Box::new(
+                GridLayout::load(path)
+                    .unwrap_or_else(|e| panic!("Could not load plant layout {}: {}", path, e)),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlantRules {
+    pub layout: PlantLayoutConfig,
+    pub seed: u64,
+    pub regrow_ticks: u64,
+}
+
+impl Default for PlantRules {
+    fn default() -> Self {
+        Self {
+            layout: PlantLayoutConfig::default(),
+            seed: 0,
+            regrow_ticks: 200,
+        }
+    }
+}
+
+/*
+ * The plants of a world. A fertile cell holds a plant unless it was eaten,
+ * in which case the plant comes back at the tick stored in regrows_at.
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PlantLayer {
+    width: usize,
+    height: usize,
+    fertile: Vec<bool>,
+    regrows_at: Vec<u64>,
+}
+
+impl PlantLayer {
+    /*
+     * Whether a cell is fertile is decided by comparing the layout density
+     * with a hash of the position and the rules seed, so the same rules give
+     * the same layer in every generation. Nothing grows on walls or water.
+     */
+    #[must_use]
+    pub fn new(width: usize, height: usize, rules: &PlantRules, terrain: &Terrain) -> Self {
+        let layout = rules.layout.build();
+        let mut fertile = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let position = Position::new(x as i32, y as i32);
+                fertile.push(
+                    terrain.get(position).is_fertile()
+                        && hash_to_unit(position.x.into(), position.y.into(), rules.seed)
+                            < layout.density(position, width, height),
+                );
+            }
+        }
+        Self {
+            width,
+            height,
+            fertile,
+            regrows_at: vec![0; width * height],
+        }
+    }
+
+    /*
+     * Uses a fixed fertile mask, for example that of a level file, instead
+     * of a layout. Nothing grows on walls or water even if the mask says so.
+     */
+    #[must_use]
+    pub fn from_fertile(width: usize, height: usize, fertile: &[bool], terrain: &Terrain) -> Self {
+        let fertile = (0..width * height)
+            .map(|i| {
+                let position = Position::new((i % width) as i32, (i / width) as i32);
+                fertile[i] && terrain.get(position).is_fertile()
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            fertile,
+            regrows_at: vec![0; width * height],
+        }
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        if position.x >= 0
+            && position.x < self.width as i32
+            && position.y >= 0
+            && position.y < self.height as i32
+        {
+            return Some(position.y as usize * self.width + position.x as usize);
+        }
+        None
+    }
+
+    #[must_use]
+    pub fn is_here(&self, position: Position, tick: u64) -> bool {
+        match self.index(position) {
+            Some(i) => self.fertile[i] && self.regrows_at[i] <= tick,
+            None => false,
+        }
+    }
+
+    pub fn consume(&mut self, position: Position, regrows_at: u64) {
+        if let Some(i) = self.index(position) {
+            self.regrows_at[i] = regrows_at;
+        }
+    }
+
+    #[must_use]
+    pub fn regrows_at(&self) -> &[u64] {
+        &self.regrows_at
+    }
+
+    pub fn set_regrows_at(&mut self, index: usize, tick: u64) {
+        self.regrows_at[index] = tick;
+    }
+}
@@ -1,8 +1,9 @@
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::ops::Sub;
 
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -38,6 +39,11 @@ impl Position {
         f64::from(self.x).mul_add(f64::from(self.x), f64::from(self.y) * f64::from(self.y)).sqrt()
     }
 
+    /*
+     * The straight-line distance, ignoring the topology and movement model
+     * of any world. Plant layouts use it so that their circles stay round on
+     * every grid, while MovementModel::distance counts moves.
+     */
     #[must_use] pub fn dist(&self, position: &Self) -> f64 {
         let diff = *position - *self;
         diff.length()
@@ -0,0 +1,72 @@
+use crate::creature::Direction;
+use serde::{Deserialize, Serialize};
+
+/*
+ * What a creature feeds on. Herbivores eat the plant they stand on, while
+ * carnivores ignore plants and live off the creatures they attack.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Diet {
+    Herbivore,
+    Carnivore,
+}
+
+impl Diet {
+    /*
+     * Herbivores are drawn as arrows and carnivores as solid triangles, both
+     * pointing the way the creature faces.
+     */
+    #[must_use]
+    pub fn glyph(self, direction: Direction) -> char {
+        match self {
+            Self::Herbivore => direction.glyph(),
+            Self::Carnivore => match direction {
+                Direction::North => '▲',
+                Direction::South => '▼',
+                Direction::East => '►',
+                Direction::West => '◄',
+                Direction::NorthEast => '◥',
+                Direction::SouthEast => '◢',
+                Direction::SouthWest => '◣',
+                Direction::NorthWest => '◤',
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn is_carnivore_glyph(glyph: char) -> bool {
+        matches!(glyph, '▲' | '▼' | '►' | '◄' | '◥' | '◢' | '◣' | '◤')
+    }
+}
+
+/*
+ * A creature whose diet effect reaches threshold is a carnivore. When it
+ * attacks, it kills a creature in its own cell or the cell it faces and
+ * gains efficiency times the life of its prey.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PredationConfig {
+    pub threshold: f64,
+    pub efficiency: f64,
+}
+
+impl Default for PredationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            efficiency: 0.5,
+        }
+    }
+}
+
+impl PredationConfig {
+    #[must_use]
+    pub fn diet(&self, diet_effect: f64) -> Diet {
+        if diet_effect >= self.threshold {
+            Diet::Carnivore
+        } else {
+            Diet::Herbivore
+        }
+    }
+}
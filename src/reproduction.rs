@@ -0,0 +1,164 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/*
+ * How the next generation is bred from the survivors. Clonal offspring copy
+ * a single parent, while sexual offspring recombine the genomes of two.
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reproduction {
+    #[default]
+    Clonal,
+    Sexual,
+}
+
+/*
+ * Steady-state reproduction inside a running world. When enabled, a living
+ * creature whose life reaches threshold splits during the step: it pays
+ * cost from its own life, and a clone of it starting with offspring_life
+ * appears in a neighbouring cell that is neither blocked nor occupied.
+ * There are no generations, and the population is limited by food instead
+ * of the carrying capacity.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContinuousReproduction {
+    pub enabled: bool,
+    pub threshold: f64,
+    pub cost: f64,
+    pub offspring_life: f64,
+}
+
+impl Default for ContinuousReproduction {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 200.0,
+            cost: 100.0,
+            offspring_life: 100.0,
+        }
+    }
+}
+
+/*
+ * How two genomes are recombined in sexual reproduction. Uniform crossover
+ * takes each gene from either parent at random. One-point crossover takes
+ * the genes before a cut from the first parent and the rest from the
+ * second, and two-point crossover takes the genes between two cuts from the
+ * second parent.
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Crossover {
+    #[default]
+    Uniform,
+    OnePoint,
+    TwoPoint,
+}
+
+impl Crossover {
+    /*
+     * The parents may differ in length. Cuts are placed within the shorter
+     * of the two, and the child keeps the length of the parent its tail is
+     * taken from.
+     */
+    pub fn apply<T: Clone>(self, a: &[T], b: &[T], rng: &mut impl Rng) -> Vec<T> {
+        let shared = a.len().min(b.len());
+        match self {
+            Self::Uniform => {
+                let tail = if rng.gen_bool(0.5) { a } else { b };
+                (0..tail.len())
+                    .map(|i| {
+                        if i < shared && rng.gen_bool(0.5) {
+                            b[i].clone()
+                        } else if i < shared {
+                            a[i].clone()
+                        } else {
+                            tail[i].clone()
+                        }
+                    })
+                    .collect()
+            }
+            Self::OnePoint => {
+                let cut = rng.gen_range(0..=shared);
+                a[..cut].iter().chain(&b[cut..]).cloned().collect()
+            }
+            Self::TwoPoint => {
+                let mut first = rng.gen_range(0..=shared);
+                let mut second = rng.gen_range(0..=shared);
+                if first > second {
+                    std::mem::swap(&mut first, &mut second);
+                }
+                a[..first]
+                    .iter()
+                    .chain(&b[first..second])
+                    .chain(&a[second..])
+                    .cloned()
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /*
+     * Genes of the first parent are below 10 and genes of the second parent
+     * are 10 and up, so every gene of a child tells where it came from.
+     */
+    fn parents() -> (Vec<u32>, Vec<u32>) {
+        ((0..5).collect(), (10..17).collect())
+    }
+
+    fn gene_from(parent: &[u32], child: &[u32], i: usize) -> bool {
+        parent.get(i) == child.get(i)
+    }
+
+    #[test]
+    fn uniform_takes_each_gene_from_either_parent() {
+        let (a, b) = parents();
+        for seed in 0..50 {
+            let child = Crossover::Uniform.apply(&a, &b, &mut StdRng::seed_from_u64(seed));
+            assert!(child.len() == a.len() || child.len() == b.len());
+            for i in 0..child.len() {
+                assert!(gene_from(&a, &child, i) || gene_from(&b, &child, i));
+            }
+        }
+    }
+
+    #[test]
+    fn one_point_joins_a_head_of_a_to_a_tail_of_b() {
+        let (a, b) = parents();
+        for seed in 0..50 {
+            let child = Crossover::OnePoint.apply(&a, &b, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(child.len(), b.len());
+            let cut = child.iter().take_while(|&&gene| gene < 10).count();
+            assert!(cut <= a.len());
+            assert_eq!(child[..cut], a[..cut]);
+            assert_eq!(child[cut..], b[cut..]);
+        }
+    }
+
+    #[test]
+    fn two_point_swaps_in_a_middle_section_of_b() {
+        let (a, b) = parents();
+        for seed in 0..50 {
+            let child = Crossover::TwoPoint.apply(&a, &b, &mut StdRng::seed_from_u64(seed));
+            assert_eq!(child.len(), a.len());
+            let first = child.iter().take_while(|&&gene| gene < 10).count();
+            let second = first
+                + child[first..]
+                    .iter()
+                    .take_while(|&&gene| gene >= 10)
+                    .count();
+            assert_eq!(child[..first], a[..first]);
+            assert_eq!(child[first..second], b[first..second]);
+            assert_eq!(child[second..], a[second..]);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::world::{World, WorldConfig, WorldLayers};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopulationConfig {
+    pub size: usize,
+    pub names: Vec<String>,
+    pub snapshot: Option<String>,
+}
+
+impl Default for PopulationConfig {
+    fn default() -> Self {
+        Self {
+            size: 100,
+            names: Vec::new(),
+            snapshot: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    pub generations: usize,
+    pub steps: usize,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            generations: 100,
+            steps: 1000,
+        }
+    }
+}
+
+/*
+ * A scenario describes everything needed to start an experiment. Every
+ * section and field is optional in the TOML file and falls back to the
+ * defaults below, so a scenario only needs to list what it changes. Fields
+ * that no section knows are refused, so that a typo is not silently
+ * replaced by a default.
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub world: WorldConfig,
+    pub population: PopulationConfig,
+    pub schedule: ScheduleConfig,
+}
+
+#[must_use]
+pub fn default_names() -> Vec<String> {
+    let data = String::from_utf8_lossy(include_bytes!("../resources/names.in"));
+    data.split('\n')
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read_to_string(path)?;
+        let scenario: Self = toml::from_str(&data)?;
+        if scenario.world.level.is_none()
+            && (scenario.world.width == 0 || scenario.world.height == 0)
+        {
+            return Err("The world must be at least one cell wide and high.".into());
+        }
+        if scenario.schedule.steps == 0 {
+            return Err("A generation must last at least one step.".into());
+        }
+        Ok(scenario)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        if self.population.names.is_empty() {
+            default_names()
+        } else {
+            self.population.names.clone()
+        }
+    }
+
+    /*
+     * Builds the first world of the scenario with its starting population in
+     * place. When a snapshot is given its creatures become the starting
+     * population, but the world itself, with its terrain and plants, is built
+     * from the scenario.
+     */
+    #[must_use]
+    pub fn build_world(&self, layers: WorldLayers, seed: u64) -> World {
+        let mut world = World::from_layers(layers, false, seed);
+        if let Some(path) = &self.population.snapshot {
+            let snapshot = World::load(path)
+                .unwrap_or_else(|e| panic!("Could not load snapshot {}: {}", path, e));
+            world.adopt_creatures(snapshot);
+            return world;
+        }
+
+        let names = self.names();
+        for _ in 0..self.population.size {
+            let name = names
+                .choose(world.rng())
+                .expect("Could not add creature.")
+                .clone();
+            world.add_creature(&name);
+        }
+        world
+    }
+}
@@ -0,0 +1,177 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/*
+ * A creature of the finished generation that may become a parent.
+ */
+pub struct Candidate {
+    pub score: f64,
+    pub alive: bool,
+}
+
+/*
+ * Decides which creatures of a finished generation become the parents of
+ * the next one. Returns count indices into candidates, one per child, or
+ * none if nobody may breed.
+ */
+pub trait SelectionStrategy {
+    fn select(&self, candidates: &[Candidate], count: usize, rng: &mut StdRng) -> Vec<usize>;
+}
+
+/*
+ * Only the survivors breed, each in turn, so every survivor gets an equal
+ * share of the next generation no matter its score.
+ */
+#[derive(Clone, Default)]
+pub struct TruncationSelection;
+
+impl SelectionStrategy for TruncationSelection {
+    fn select(&self, candidates: &[Candidate], count: usize, _rng: &mut StdRng) -> Vec<usize> {
+        let survivors: Vec<usize> = (0..candidates.len())
+            .filter(|&i| candidates[i].alive)
+            .collect();
+        if survivors.is_empty() {
+            return Vec::new();
+        }
+        survivors.into_iter().cycle().take(count).collect()
+    }
+}
+
+/*
+ * Every creature is picked with a probability proportional to its score.
+ * Negative scores count as zero, and if nobody scores above zero every
+ * creature is equally likely.
+ */
+#[derive(Clone, Default)]
+pub struct ProportionalSelection;
+
+impl SelectionStrategy for ProportionalSelection {
+    fn select(&self, candidates: &[Candidate], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let weights: Vec<f64> = candidates.iter().map(|c| c.score.max(0.0)).collect();
+        weighted(&weights, count, rng)
+    }
+}
+
+/*
+ * Each parent is the best of size creatures drawn at random.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TournamentSelection {
+    pub size: usize,
+}
+
+impl Default for TournamentSelection {
+    fn default() -> Self {
+        Self { size: 3 }
+    }
+}
+
+impl SelectionStrategy for TournamentSelection {
+    fn select(&self, candidates: &[Candidate], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+        (0..count)
+            .map(|_| {
+                (0..self.size.max(1))
+                    .map(|_| rng.gen_range(0..candidates.len()))
+                    .max_by(|&a, &b| candidates[a].score.total_cmp(&candidates[b].score))
+                    .expect("Could not hold tournament.")
+            })
+            .collect()
+    }
+}
+
+/*
+ * Every creature is picked with a probability proportional to its rank, so
+ * the worst has weight 1 and the best has weight n. Unlike proportional
+ * selection, a single outstanding score cannot take over the population.
+ */
+#[derive(Clone, Default)]
+pub struct RankSelection;
+
+impl SelectionStrategy for RankSelection {
+    fn select(&self, candidates: &[Candidate], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| candidates[a].score.total_cmp(&candidates[b].score));
+        let mut weights = vec![0.0; candidates.len()];
+        for (rank, &i) in order.iter().enumerate() {
+            weights[i] = (rank + 1) as f64;
+        }
+        weighted(&weights, count, rng)
+    }
+}
+
+/*
+ * The elites best creatures are each guaranteed one child, and the rest of
+ * the parents are chosen by another strategy.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElitismSelection {
+    pub elites: usize,
+    pub then: Box<SelectionConfig>,
+}
+
+impl Default for ElitismSelection {
+    fn default() -> Self {
+        Self {
+            elites: 5,
+            then: Box::new(SelectionConfig::Truncation),
+        }
+    }
+}
+
+impl SelectionStrategy for ElitismSelection {
+    fn select(&self, candidates: &[Candidate], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| candidates[b].score.total_cmp(&candidates[a].score));
+        order.truncate(self.elites.min(count));
+        let rest = self
+            .then
+            .build()
+            .select(candidates, count - order.len(), rng);
+        order.extend(rest);
+        order
+    }
+}
+
+fn weighted(weights: &[f64], count: usize, rng: &mut StdRng) -> Vec<usize> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    match WeightedIndex::new(weights) {
+        Ok(distribution) => (0..count).map(|_| distribution.sample(rng)).collect(),
+        Err(_) => (0..count)
+            .map(|_| rng.gen_range(0..weights.len()))
+            .collect(),
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SelectionConfig {
+    #[default]
+    Truncation,
+    FitnessProportional,
+    Tournament(TournamentSelection),
+    Rank,
+    Elitism(ElitismSelection),
+}
+
+impl SelectionConfig {
+    #[must_use]
+    pub fn build(&self) -> Box<dyn SelectionStrategy> {
+        match self {
+            Self::Truncation => Box::new(TruncationSelection),
+            Self::FitnessProportional => Box::new(ProportionalSelection),
+            Self::Tournament(selection) => Box::new(selection.clone()),
+            Self::Rank => Box::new(RankSelection),
+            Self::Elitism(selection) => Box::new(selection.clone()),
+        }
+    }
+}
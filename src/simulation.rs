@@ -0,0 +1,154 @@
+use crate::fitness::Fitness;
+use crate::genealogy::Genealogy;
+use crate::scenario::Scenario;
+use crate::stats::GenerationStats;
+use crate::world::{World, WorldLayers};
+use log::info;
+
+pub struct Simulation {
+    world: World,
+    layers: WorldLayers,
+    scenario: Scenario,
+    seed: u64,
+    generation: usize,
+    genealogy: Genealogy,
+}
+
+impl Simulation {
+    /*
+     * The terrain and plants are built once from the scenario, and every
+     * generation starts from a copy of them.
+     */
+    #[must_use]
+    pub fn new(scenario: Scenario, seed: u64) -> Self {
+        let layers = WorldLayers::build(scenario.world.clone());
+        let mut simulation = Self {
+            world: scenario.build_world(layers.clone(), seed),
+            layers,
+            scenario,
+            seed,
+            generation: 0,
+            genealogy: Genealogy::default(),
+        };
+        simulation.record_genealogy();
+        simulation
+    }
+
+    fn record_genealogy(&mut self) {
+        for creature in &self.world.current_state.creatures {
+            self.genealogy.record(creature);
+        }
+    }
+
+    /*
+     * Replaces the current world with a fresh one populated by the survivors
+     * of the previous generation. The new world has not been stepped yet.
+     */
+    pub fn next_generation(&mut self, save_history: bool) {
+        let world = World::from_layers(self.layers.clone(), save_history, self.seed);
+        let old_world = std::mem::replace(&mut self.world, world);
+        self.world.add_creatures_from_world(old_world);
+        self.record_genealogy();
+    }
+
+    pub fn step(&mut self) {
+        self.world.step();
+    }
+
+    /*
+     * Scores creatures with the given fitness instead of the one in the
+     * scenario. It is handed from each generation to the next.
+     */
+    pub fn set_fitness(&mut self, fitness: Box<dyn Fitness>) {
+        self.world.set_fitness(fitness);
+    }
+
+    /*
+     * In continuous reproduction there is no new world for each generation.
+     * The same world runs on, and a generation is just a reporting period
+     * of the same number of steps.
+     */
+    pub fn run_generation(&mut self, save_history: bool) -> GenerationStats {
+        if self.scenario.world.continuous.enabled {
+            self.world.begin_period(save_history);
+            self.world.simulate(self.scenario.schedule.steps);
+            self.record_genealogy();
+        } else {
+            self.next_generation(save_history);
+            self.world.simulate(self.scenario.schedule.steps);
+        }
+
+        let stats = GenerationStats::collect(
+            self.generation,
+            &self.scenario.world,
+            &self.world.current_state,
+        );
+        if self.scenario.world.continuous.enabled {
+            info!(
+                "{} creatures are alive after period {}.",
+                stats.alive, self.generation,
+            );
+        } else {
+            info!(
+                "{} creatures survived generation {} ({}%).",
+                stats.alive,
+                self.generation,
+                100 * stats.alive / self.carrying_capacity().max(1),
+            );
+        }
+        self.generation += 1;
+        stats
+    }
+
+    #[must_use]
+    pub fn genealogy(&self) -> &Genealogy {
+        &self.genealogy
+    }
+
+    #[must_use]
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    #[must_use]
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    #[must_use]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    #[must_use]
+    pub fn carrying_capacity(&self) -> usize {
+        self.scenario.world.carrying_capacity
+    }
+
+    #[must_use]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64) -> String {
+        let mut scenario = Scenario::default();
+        scenario.schedule.steps = 200;
+        let mut simulation = Simulation::new(scenario, seed);
+        for _ in 0..3 {
+            simulation.run_generation(false);
+        }
+        serde_json::to_string(&simulation.world().current_state)
+            .expect("Could not serialize state.")
+    }
+
+    #[test]
+    fn same_seed_gives_same_run() {
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::creature::Creature;
+use crate::genome::Genome;
+use crate::traits::TraitRegistry;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/*
+ * How genomes are grouped into species. The distance between two genomes is
+ * the edit distance between their behavior patterns times behavior_weight,
+ * plus the difference of each trait value as a fraction of its range times
+ * trait_weight. A genome joins the first species whose representative is at
+ * most threshold away.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeciesConfig {
+    pub threshold: f64,
+    pub behavior_weight: f64,
+    pub trait_weight: f64,
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 3.0,
+            behavior_weight: 1.0,
+            trait_weight: 1.0,
+        }
+    }
+}
+
+impl SpeciesConfig {
+    #[must_use]
+    pub fn distance(&self, a: &Genome, b: &Genome, traits: &TraitRegistry) -> f64 {
+        let behavior = edit_distance(&a.behavior.action_pattern, &b.behavior.action_pattern);
+        self.behavior_weight * behavior as f64 + self.trait_distance(a, b, traits)
+    }
+
+    /*
+     * Whether two genomes are at most threshold apart. The edit distance is
+     * at least the difference in length of the patterns, so most distant
+     * pairs are ruled out without computing it.
+     */
+    #[must_use]
+    pub fn within_threshold(&self, a: &Genome, b: &Genome, traits: &TraitRegistry) -> bool {
+        let (a_pattern, b_pattern) = (&a.behavior.action_pattern, &b.behavior.action_pattern);
+        let traits = self.trait_distance(a, b, traits);
+        let lower_bound = self.behavior_weight * a_pattern.len().abs_diff(b_pattern.len()) as f64;
+        if traits + lower_bound > self.threshold {
+            return false;
+        }
+        traits + self.behavior_weight * edit_distance(a_pattern, b_pattern) as f64 <= self.threshold
+    }
+
+    fn trait_distance(&self, a: &Genome, b: &Genome, traits: &TraitRegistry) -> f64 {
+        let mut trait_distance = 0.0;
+        for (i, t) in traits.traits().iter().enumerate() {
+            let range = f64::from((t.max - t.min).max(1));
+            let difference = traits.value(&a.trait_set, i) - traits.value(&b.trait_set, i);
+            trait_distance += f64::from(difference.abs()) / range;
+        }
+        self.trait_weight * trait_distance
+    }
+}
+
+fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, x) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+struct Species {
+    id: u64,
+    representative: Genome,
+}
+
+/*
+ * The species alive in a world. Each species is represented by the genome
+ * of one of its members, and keeps its id for as long as it has living
+ * members, so ids are stable across generations.
+ */
+#[derive(Default)]
+pub struct SpeciesTracker {
+    species: Vec<Species>,
+    next_id: u64,
+}
+
+impl SpeciesTracker {
+    /*
+     * Rebuilds the tracker from creatures that already carry species ids,
+     * such as those of a loaded snapshot.
+     */
+    #[must_use]
+    pub fn from_creatures(creatures: &[Creature]) -> Self {
+        let mut tracker = Self::default();
+        for creature in creatures.iter().filter(|c| c.is_alive()) {
+            if !tracker.species.iter().any(|s| s.id == creature.species) {
+                tracker.species.push(Species {
+                    id: creature.species,
+                    representative: creature.genome.clone(),
+                });
+                tracker.next_id = tracker.next_id.max(creature.species + 1);
+            }
+        }
+        tracker
+    }
+
+    #[must_use]
+    pub fn assign(
+        &mut self,
+        genome: &Genome,
+        config: &SpeciesConfig,
+        traits: &TraitRegistry,
+    ) -> u64 {
+        for species in &self.species {
+            if config.within_threshold(genome, &species.representative, traits) {
+                return species.id;
+            }
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.species.push(Species {
+            id,
+            representative: genome.clone(),
+        });
+        info!("Species {} has appeared.", id);
+        id
+    }
+
+    /*
+     * Drops the species without living members, and moves the
+     * representative of every other species to its living member closest
+     * to the old representative, so that species follow their members as
+     * they evolve.
+     */
+    pub fn update(
+        &mut self,
+        creatures: &[Creature],
+        config: &SpeciesConfig,
+        traits: &TraitRegistry,
+    ) {
+        let mut members: HashMap<u64, Vec<&Creature>> = HashMap::new();
+        for creature in creatures.iter().filter(|c| c.is_alive()) {
+            members.entry(creature.species).or_default().push(creature);
+        }
+        self.species
+            .retain_mut(|species| match members.get(&species.id) {
+                Some(members) => {
+                    let closest = members
+                        .iter()
+                        .min_by(|a, b| {
+                            let a = config.distance(&a.genome, &species.representative, traits);
+                            let b = config.distance(&b.genome, &species.representative, traits);
+                            a.total_cmp(&b)
+                        })
+                        .expect("Could not find species member.");
+                    species.representative = closest.genome.clone();
+                    true
+                }
+                None => {
+                    info!("Species {} has gone extinct.", species.id);
+                    false
+                }
+            });
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.species.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.species.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(edit_distance::<u8>(&[], &[]), 0);
+        assert_eq!(edit_distance(&[1, 2, 3], &[]), 3);
+        assert_eq!(edit_distance(&[], &[1, 2]), 2);
+        assert_eq!(edit_distance(&[1, 2, 3], &[1, 2, 3]), 0);
+        assert_eq!(edit_distance(&[1, 2, 3], &[1, 4, 3]), 1);
+        assert_eq!(edit_distance(&[1, 2, 3], &[1, 3]), 1);
+        assert_eq!(edit_distance(&[1, 3], &[1, 2, 3]), 1);
+        assert_eq!(edit_distance(&[1, 2, 3, 4], &[2, 3, 4, 5]), 2);
+        assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+    }
+}
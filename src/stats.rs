@@ -0,0 +1,120 @@
+use crate::predation::Diet;
+use crate::traits::TraitRegistry;
+use crate::world::{WorldConfig, WorldState};
+use std::collections::HashSet;
+
+/*
+ * A summary of the survivors at the end of a generation, laid out so that it
+ * can be written as one CSV row.
+ */
+pub struct GenerationStats {
+    pub generation: usize,
+    pub alive: usize,
+    pub trait_means: Vec<f64>,
+    pub trait_variances: Vec<f64>,
+    pub behaviors: usize,
+    pub dominant_strain: Option<(u64, u64)>,
+    pub species: usize,
+    pub dominant_species: Option<(u64, u64)>,
+    pub carnivores: usize,
+}
+
+impl GenerationStats {
+    #[must_use]
+    pub fn collect(generation: usize, config: &WorldConfig, state: &WorldState) -> Self {
+        let traits = &config.traits;
+        let alive: Vec<_> = state.creatures.iter().filter(|c| c.is_alive()).collect();
+        let num_traits = traits.len();
+
+        let mut trait_means = vec![0.0; num_traits];
+        let mut trait_variances = vec![0.0; num_traits];
+        if !alive.is_empty() {
+            let n = alive.len() as f64;
+            for creature in &alive {
+                for (i, mean) in trait_means.iter_mut().enumerate() {
+                    *mean += f64::from(traits.value(&creature.genome.trait_set, i));
+                }
+            }
+            for mean in &mut trait_means {
+                *mean /= n;
+            }
+            for creature in &alive {
+                for (i, variance) in trait_variances.iter_mut().enumerate() {
+                    let diff =
+                        f64::from(traits.value(&creature.genome.trait_set, i)) - trait_means[i];
+                    *variance += diff * diff;
+                }
+            }
+            for variance in &mut trait_variances {
+                *variance /= n;
+            }
+        }
+
+        let species_counts = state.species_counts();
+        let behaviors: HashSet<String> = alive
+            .iter()
+            .map(|c| c.genome.behavior.to_string())
+            .collect();
+        let carnivores = alive
+            .iter()
+            .filter(|c| c.diet(traits, &config.predation) == Diet::Carnivore)
+            .count();
+
+        Self {
+            generation,
+            alive: alive.len(),
+            trait_means,
+            trait_variances,
+            behaviors: behaviors.len(),
+            dominant_strain: state.strain_counts().first().copied(),
+            species: species_counts.len(),
+            dominant_species: species_counts.first().copied(),
+            carnivores,
+        }
+    }
+
+    #[must_use]
+    pub fn csv_header(traits: &TraitRegistry) -> String {
+        let mut columns = vec!["generation".to_string(), "alive".to_string()];
+        for t in traits.traits() {
+            columns.push(format!("{}_mean", t.name));
+            columns.push(format!("{}_variance", t.name));
+        }
+        columns.push("behaviors".to_string());
+        columns.push("dominant_strain".to_string());
+        columns.push("dominant_strain_count".to_string());
+        columns.push("species".to_string());
+        columns.push("dominant_species".to_string());
+        columns.push("dominant_species_count".to_string());
+        columns.push("carnivores".to_string());
+        columns.join(",")
+    }
+
+    #[must_use]
+    pub fn csv_row(&self) -> String {
+        let mut columns = vec![self.generation.to_string(), self.alive.to_string()];
+        for (mean, variance) in self.trait_means.iter().zip(&self.trait_variances) {
+            columns.push(mean.to_string());
+            columns.push(variance.to_string());
+        }
+        columns.push(self.behaviors.to_string());
+        push_dominant(&mut columns, self.dominant_strain);
+        columns.push(self.species.to_string());
+        push_dominant(&mut columns, self.dominant_species);
+        columns.push(self.carnivores.to_string());
+        columns.join(",")
+    }
+}
+
+fn push_dominant(columns: &mut Vec<String>, dominant: Option<(u64, u64)>) {
+    match dominant {
+        Some((id, count)) => {
+            columns.push(id.to_string());
+            columns.push(count.to_string());
+        }
+        None => {
+            columns.push(String::new());
+            columns.push("0".to_string());
+        }
+    }
+}
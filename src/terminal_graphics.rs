@@ -1,8 +1,8 @@
 use crate::map;
 use crate::position::Position;
+use crate::predation::Diet;
 use crate::world;
 use crossterm::event::{self, Event, KeyCode};
-use std::collections::HashMap;
 use std::time::Duration;
 use tui::{
     backend::Backend,
@@ -46,7 +46,7 @@ pub fn display<B: Backend>(
     frame_delay: u64,
     cursor: &Cursor,
     world_state: &world::WorldState,
-    show_dead: bool,
+    info: &[String],
 ) -> Interaction {
     terminal
         .draw(|f| {
@@ -66,20 +66,24 @@ pub fn display<B: Backend>(
                 .paint(|ctx| {
                     for x in 0..map.width {
                         for y in 0..map.height {
-                            let mut tmp = [0];
-                            let shade = map.slots[y as usize][x as usize].1 as u8;
-                            let color = match map.slots[y as usize][x as usize].0 {
+                            let shade = map.slots[y][x].1 as u8;
+                            let color = match map.slots[y][x].0 {
                                 '.' => Color::Green,
                                 ' ' => Color::Reset,
+                                '#' => Color::Gray,
+                                '~' => Color::Blue,
+                                ',' => Color::Rgb(120, 90, 40),
                                 'x' => Color::Rgb(30, 30, 30),
+                                c if Diet::is_carnivore_glyph(c) => {
+                                    Color::Rgb(shade, shade / 4, shade / 4)
+                                }
                                 _ => Color::Rgb(shade, shade, shade),
                             };
                             ctx.print(
                                 x as f64,
                                 y as f64,
                                 Span::styled(
-                                    String::new()
-                                        + map.slots[y as usize][x as usize].0.encode_utf8(&mut tmp),
+                                    map.slots[y][x].0.to_string(),
                                     Style::default().fg(color),
                                 ),
                             );
@@ -122,47 +126,19 @@ pub fn display<B: Backend>(
             f.render_widget(canvas, size);
 
             let mut info_box_text = Vec::new();
-            for creature in world_state.get_creatures_at(Position::new(cursor.x, cursor.y)) {
-                if creature.is_alive() || show_dead {
-                    info_box_text.push(Spans::from(format!("{}", creature)))
-                }
+            for line in info {
+                info_box_text.push(Spans::from(line.as_str()))
             }
             let info_box = Paragraph::new(info_box_text)
                 .block(Block::default().title("Info").borders(Borders::ALL))
                 .wrap(Wrap { trim: true });
 
-            let mut histogram: HashMap<u64, u64> = HashMap::new();
-            for creature in &world_state.creatures {
-                if creature.is_alive() {
-                    if histogram.get(&creature.strain).is_none() {
-                        histogram.insert(creature.strain, 0);
-                    }
-                    let count = histogram.get(&creature.strain);
-                    histogram.insert(
-                        creature.strain,
-                        count.expect("Could not get strain count.") + 1,
-                    );
-                }
-            }
-            let mut leaderboard_values = Vec::new();
-            for element in histogram {
-                leaderboard_values.push(element)
-            }
-            leaderboard_values.sort_by(|a, b| {
-                if a.1 == b.1 {
-                    b.0.partial_cmp(&a.0)
-                        .expect("Could not perform comparison.")
-                } else {
-                    b.1.partial_cmp(&a.1)
-                        .expect("Could not perform comparison.")
-                }
-            });
             let mut leaderboard_text = Vec::new();
-            for element in leaderboard_values {
+            for element in world_state.species_counts() {
                 leaderboard_text.push(Spans::from(format!("{} {}", element.0, element.1)))
             }
             let leaderboard = Paragraph::new(leaderboard_text)
-                .block(Block::default().title("Leaderboard").borders(Borders::ALL))
+                .block(Block::default().title("Species").borders(Borders::ALL))
                 .wrap(Wrap { trim: true });
 
             if f.size().height - size.height > 10 {
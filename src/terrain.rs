@@ -0,0 +1,179 @@
+use crate::position::Position;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cell {
+    #[default]
+    Open,
+    Rough,
+    Water,
+    Wall,
+}
+
+impl Cell {
+    #[must_use]
+    pub fn blocks(self) -> bool {
+        self == Self::Wall
+    }
+
+    #[must_use]
+    pub fn is_fertile(self) -> bool {
+        self == Self::Open || self == Self::Rough
+    }
+
+    #[must_use]
+    pub fn glyph(self) -> char {
+        match self {
+            Self::Open => ' ',
+            Self::Rough => ',',
+            Self::Water => '~',
+            Self::Wall => '#',
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub cell: Cell,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TerrainConfig {
+    pub border: bool,
+    pub rough_cost: f64,
+    pub water_cost: f64,
+    pub regions: Vec<Region>,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self {
+            border: false,
+            rough_cost: 1.0,
+            water_cost: 5.0,
+            regions: Vec::new(),
+        }
+    }
+}
+
+/*
+ * The ground of a world. Walls cannot be entered, and entering rough ground
+ * or water costs the creature extra life on top of its normal aging.
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Terrain {
+    width: usize,
+    height: usize,
+    rough_cost: f64,
+    water_cost: f64,
+    cells: Vec<Cell>,
+}
+
+impl Terrain {
+    #[must_use]
+    pub fn new(width: usize, height: usize, config: &TerrainConfig) -> Self {
+        Self::from_cells(width, height, vec![Cell::Open; width * height], config)
+    }
+
+    /*
+     * Starts from the given cells, for example those of a level file, and
+     * applies the regions and border of the config on top of them.
+     */
+    #[must_use]
+    pub fn from_cells(
+        width: usize,
+        height: usize,
+        cells: Vec<Cell>,
+        config: &TerrainConfig,
+    ) -> Self {
+        let mut terrain = Self {
+            width,
+            height,
+            rough_cost: config.rough_cost,
+            water_cost: config.water_cost,
+            cells,
+        };
+        for region in &config.regions {
+            for x in region.x..region.x + region.width {
+                for y in region.y..region.y + region.height {
+                    terrain.set(Position::new(x, y), region.cell);
+                }
+            }
+        }
+        if config.border {
+            for x in 0..width as i32 {
+                terrain.set(Position::new(x, 0), Cell::Wall);
+                terrain.set(Position::new(x, height as i32 - 1), Cell::Wall);
+            }
+            for y in 0..height as i32 {
+                terrain.set(Position::new(0, y), Cell::Wall);
+                terrain.set(Position::new(width as i32 - 1, y), Cell::Wall);
+            }
+        }
+        terrain
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        if position.x >= 0
+            && position.x < self.width as i32
+            && position.y >= 0
+            && position.y < self.height as i32
+        {
+            return Some(position.y as usize * self.width + position.x as usize);
+        }
+        None
+    }
+
+    #[must_use]
+    pub fn get(&self, position: Position) -> Cell {
+        match self.index(position) {
+            Some(i) => self.cells[i],
+            None => Cell::Open,
+        }
+    }
+
+    pub fn set(&mut self, position: Position, cell: Cell) {
+        if let Some(i) = self.index(position) {
+            self.cells[i] = cell;
+        }
+    }
+
+    #[must_use]
+    pub fn move_cost(&self, position: Position) -> f64 {
+        match self.get(position) {
+            Cell::Rough => self.rough_cost,
+            Cell::Water => self.water_cost,
+            Cell::Open | Cell::Wall => 0.0,
+        }
+    }
+
+    /*
+     * Finds the cell closest to the position that can be entered, searching
+     * outwards in growing squares.
+     */
+    #[must_use]
+    pub fn nearest_open(&self, position: Position) -> Position {
+        let max_radius = self.width.max(self.height) as i32;
+        for radius in 0..=max_radius {
+            for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    if dx.abs() != radius && dy.abs() != radius {
+                        continue;
+                    }
+                    let candidate = Position::new(position.x + dx, position.y + dy);
+                    if self.index(candidate).is_some() && !self.get(candidate).blocks() {
+                        return candidate;
+                    }
+                }
+            }
+        }
+        position
+    }
+}
@@ -0,0 +1,40 @@
+use crate::position::Position;
+use serde::{Deserialize, Serialize};
+
+/*
+ * What happens at the edges of a world. On a torus the edges wrap around to
+ * the opposite side. Walls stop a creature at the edge, and reflecting edges
+ * stop it and turn it around.
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topology {
+    #[default]
+    Torus,
+    Walls,
+    Reflect,
+}
+
+impl Topology {
+    /*
+     * Maps a position that may lie outside of the world onto the cell it
+     * refers to, or None if the edge cannot be crossed.
+     */
+    #[must_use]
+    pub fn wrap(self, position: Position, width: usize, height: usize) -> Option<Position> {
+        let (width, height) = (width as i32, height as i32);
+        match self {
+            Self::Torus => Some(Position::new(
+                position.x.rem_euclid(width),
+                position.y.rem_euclid(height),
+            )),
+            Self::Walls | Self::Reflect => {
+                if position.x >= 0 && position.x < width && position.y >= 0 && position.y < height {
+                    Some(position)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/*
+ * What a trait does to the creature that carries it. The values of all
+ * traits with the same effect are added up, and an effect that no trait
+ * provides falls back to the trait of the default registry.
+ *
+ *   none                - no effect; the trait evolves freely
+ *   aging_speed_divisor - each tick costs 1 + 1 / value life
+ *   eating_efficiency   - the life gained from eating a plant
+ *   hunger_threshold    - the life below which the low life sensor reads true
+ *   diet                - carnivore at or above the predation threshold
+ */
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraitEffect {
+    #[default]
+    None,
+    AgingSpeedDivisor,
+    EatingEfficiency,
+    HungerThreshold,
+    Diet,
+}
+
+/*
+ * The declaration of one trait. A creature carries an integer value for it
+ * between min and max, and the effect sees that value times weight.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraitDef {
+    pub name: String,
+    pub min: i32,
+    pub max: i32,
+    pub weight: f64,
+    pub initial: i32,
+    pub effect: TraitEffect,
+}
+
+impl Default for TraitDef {
+    fn default() -> Self {
+        Self {
+            name: "trait".to_string(),
+            min: 1,
+            max: 10,
+            weight: 1.0,
+            initial: 5,
+            effect: TraitEffect::None,
+        }
+    }
+}
+
+impl TraitDef {
+    fn new(name: &str, initial: i32, weight: f64, effect: TraitEffect) -> Self {
+        Self {
+            name: name.to_string(),
+            initial,
+            weight,
+            effect,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: i32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+}
+
+/*
+ * The traits of a world, in the order their values are stored in a
+ * TraitSet.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TraitRegistry {
+    traits: Vec<TraitDef>,
+}
+
+impl Default for TraitRegistry {
+    fn default() -> Self {
+        Self {
+            traits: vec![
+                TraitDef::new(
+                    "aging_speed_divisor",
+                    5,
+                    0.2,
+                    TraitEffect::AgingSpeedDivisor,
+                ),
+                TraitDef::new("eating_efficiency", 5, 50.0, TraitEffect::EatingEfficiency),
+                TraitDef::new("hunger_threshold", 4, 25.5, TraitEffect::HungerThreshold),
+                TraitDef {
+                    min: 0,
+                    ..TraitDef::new("diet", 0, 0.1, TraitEffect::Diet)
+                },
+            ],
+        }
+    }
+}
+
+impl TraitRegistry {
+    #[must_use]
+    pub fn traits(&self) -> &[TraitDef] {
+        &self.traits
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.traits.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.traits.is_empty()
+    }
+
+    #[must_use]
+    pub fn initial_set(&self) -> TraitSet {
+        TraitSet {
+            values: self.traits.iter().map(|t| t.initial).collect(),
+        }
+    }
+
+    /*
+     * The value of the i-th trait in the set, or its initial value if the
+     * set was made for a registry with fewer traits.
+     */
+    #[must_use]
+    pub fn value(&self, set: &TraitSet, i: usize) -> i32 {
+        set.values.get(i).copied().unwrap_or(self.traits[i].initial)
+    }
+
+    /*
+     * The traits in the set as name=value pairs separated by spaces, in the
+     * order of the registry.
+     */
+    #[must_use]
+    pub fn format(&self, set: &TraitSet) -> String {
+        self.traits
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("{}={}", t.name, self.value(set, i)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /*
+     * The summed effect of the traits in the set. An effect that no trait
+     * provides takes the initial values of the default registry, which is
+     * built only once.
+     */
+    #[must_use]
+    pub fn effect(&self, effect: TraitEffect, set: &TraitSet) -> f64 {
+        self.sum(effect, set).unwrap_or_else(|| {
+            static FALLBACK: OnceLock<TraitRegistry> = OnceLock::new();
+            let fallback = FALLBACK.get_or_init(Self::default);
+            fallback.sum(effect, &TraitSet::default()).unwrap_or(0.0)
+        })
+    }
+
+    fn sum(&self, effect: TraitEffect, set: &TraitSet) -> Option<f64> {
+        let mut total = None;
+        for (i, t) in self.traits.iter().enumerate() {
+            if t.effect == effect {
+                *total.get_or_insert(0.0) += f64::from(self.value(set, i)) * t.weight;
+            }
+        }
+        total
+    }
+}
+
+/*
+ * The trait values of one creature, in the order of the TraitRegistry of
+ * its world.
+ */
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TraitSet {
+    pub values: Vec<i32>,
+}
+
+impl TraitSet {
+    /*
+     * Makes the set match the registry, for example after a snapshot is
+     * loaded into a world with other traits: missing values get their
+     * initial value, extra values are dropped and every value is clamped to
+     * its range.
+     */
+    pub fn conform(&mut self, registry: &TraitRegistry) {
+        self.values.truncate(registry.len());
+        for t in &registry.traits[self.values.len()..] {
+            self.values.push(t.initial);
+        }
+        for (value, t) in self.values.iter_mut().zip(&registry.traits) {
+            *value = (*value).clamp(t.min, t.max.max(t.min));
+        }
+    }
+}
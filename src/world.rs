@@ -1,38 +1,110 @@
-use crate::creature::Creature;
+use crate::creature::{Creature, Surroundings};
+use crate::fitness::{Fitness, FitnessConfig};
+use crate::genome::{BehaviorRules, Genome, MutationRates};
+use crate::history::History;
+use crate::level::Level;
 use crate::map::Map;
+use crate::movement::MovementModel;
+use crate::plant::{PlantLayer, PlantRules};
 use crate::position::Position;
+use crate::predation::PredationConfig;
+use crate::reproduction::{ContinuousReproduction, Crossover, Reproduction};
+use crate::selection::{Candidate, SelectionConfig};
+use crate::species::{SpeciesConfig, SpeciesTracker};
 use crate::terminal_graphics;
 use crate::terminal_graphics::Cursor;
 use crate::terminal_graphics::Interaction;
+use crate::terrain::{Terrain, TerrainConfig};
+use crate::topology::Topology;
+use crate::traits::TraitRegistry;
 use crate::DisplayMode;
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
 use std::io;
+use std::io::{BufReader, BufWriter};
 use tui::{backend::CrosstermBackend, Terminal};
 
-#[must_use]
-pub fn plant_is_here(position: Position) -> bool {
-    position.rand() % 4 == 0
-        && position.dist(&Position::new(40, 15)) > 6.0
-        && position.x != 40
-        && position.y != 15
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldConfig {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub carrying_capacity: usize,
+    pub plants: PlantRules,
+    pub terrain: TerrainConfig,
+    pub topology: Topology,
+    pub movement: MovementModel,
+    pub traits: TraitRegistry,
+    pub behavior: BehaviorRules,
+    pub mutation: MutationRates,
+    pub reproduction: Reproduction,
+    pub crossover: Crossover,
+    pub continuous: ContinuousReproduction,
+    pub predation: PredationConfig,
+    pub selection: SelectionConfig,
+    pub fitness: FitnessConfig,
+    pub species: SpeciesConfig,
+    pub level: Option<String>,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            name: "World".to_string(),
+            width: 80,
+            height: 30,
+            carrying_capacity: 100,
+            plants: PlantRules::default(),
+            terrain: TerrainConfig::default(),
+            topology: Topology::default(),
+            movement: MovementModel::default(),
+            traits: TraitRegistry::default(),
+            behavior: BehaviorRules::default(),
+            mutation: MutationRates::default(),
+            reproduction: Reproduction::default(),
+            crossover: Crossover::default(),
+            continuous: ContinuousReproduction::default(),
+            predation: PredationConfig::default(),
+            selection: SelectionConfig::default(),
+            fitness: FitnessConfig::default(),
+            species: SpeciesConfig::default(),
+            level: None,
+        }
+    }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct WorldState {
     pub creatures: Vec<Creature>,
+    pub plants: PlantLayer,
+    pub tick: u64,
 }
 
 impl WorldState {
     #[must_use]
-    pub fn new() -> Self {
+    pub fn new(plants: PlantLayer) -> Self {
         Self {
             creatures: Vec::new(),
+            plants,
+            tick: 0,
         }
     }
 
+    #[must_use]
+    pub fn plant_is_here(&self, position: Position) -> bool {
+        self.plants.is_here(position, self.tick)
+    }
+
     #[must_use]
     pub fn get_creatures_at(&self, position: Position) -> Vec<&Creature> {
         let mut creatures = Vec::new();
@@ -44,6 +116,35 @@ impl WorldState {
         creatures
     }
 
+    /*
+     * Counts the living members of each strain, most populous first. Strains
+     * of equal size are ordered by descending id.
+     */
+    #[must_use]
+    pub fn strain_counts(&self) -> Vec<(u64, u64)> {
+        self.counts_by(|creature| creature.strain)
+    }
+
+    /*
+     * Counts the living members of each species, ordered like strain_counts.
+     */
+    #[must_use]
+    pub fn species_counts(&self) -> Vec<(u64, u64)> {
+        self.counts_by(|creature| creature.species)
+    }
+
+    fn counts_by(&self, key: impl Fn(&Creature) -> u64) -> Vec<(u64, u64)> {
+        let mut histogram: HashMap<u64, u64> = HashMap::new();
+        for creature in &self.creatures {
+            if creature.is_alive() {
+                *histogram.entry(key(creature)).or_insert(0) += 1;
+            }
+        }
+        let mut counts: Vec<(u64, u64)> = histogram.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+        counts
+    }
+
     #[must_use]
     pub fn num_alive(&self) -> usize {
         let mut num = 0;
@@ -56,112 +157,543 @@ impl WorldState {
     }
 }
 
+/*
+ * The on-disk snapshot of a world, stored as JSON:
+ *
+ *   version        - SNAPSHOT_VERSION at the time the file was written
+ *   config         - the WorldConfig the world was built from
+ *   terrain        - the Terrain of the world, so that snapshots of worlds
+ *                    built from a level file do not need the file
+ *   creature_count - the number of ids handed out so far
+ *   state          - the WorldState with every creature and its genome
+ *
+ * The version is bumped whenever the layout of any of these changes, and
+ * snapshots with a different version are rejected on load.
+ */
+pub const SNAPSHOT_VERSION: u32 = 5;
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    config: WorldConfig,
+    terrain: Terrain,
+    creature_count: usize,
+    state: WorldState,
+}
+
+/*
+ * A replay file holds the recorded history of one world as JSON:
+ *
+ *   version - REPLAY_VERSION at the time the file was written
+ *   config  - the WorldConfig the world was built from
+ *   terrain - the Terrain of the world, so that replays of worlds built
+ *             from a level file do not need the file
+ *   history - the History of the world, one frame per simulation step,
+ *             stored as keyframes and per-step deltas
+ *
+ * Like the snapshot version, the version is bumped whenever the layout of
+ * any of these changes.
+ */
+pub const REPLAY_VERSION: u32 = 6;
+
+#[derive(Serialize, Deserialize)]
+struct Replay {
+    version: u32,
+    config: WorldConfig,
+    terrain: Terrain,
+    history: History,
+}
+
 pub struct World {
-    pub name: String,
-    pub history: Vec<WorldState>,
+    pub config: WorldConfig,
+    pub terrain: Terrain,
+    pub history: History,
     pub current_state: WorldState,
-    carrying_capacity: usize,
-    width: usize,
-    height: usize,
     creature_count: usize,
     save_history: bool,
+    rng: StdRng,
+    species: SpeciesTracker,
+    fitness: Box<dyn Fitness>,
+}
+
+/*
+ * The terrain and starting plants of a world, which depend only on its
+ * config. Building them may read a level or plant layout file, so a
+ * simulation builds them once and gives every new world a copy.
+ */
+#[derive(Clone)]
+pub struct WorldLayers {
+    config: WorldConfig,
+    terrain: Terrain,
+    plants: PlantLayer,
+}
+
+impl WorldLayers {
+    /*
+     * A level file replaces the width, height and plant layout of the config
+     * with its own; the terrain regions and border still apply on top of it.
+     * A hex grid on a torus needs an even height, or the shifted rows would
+     * not line up across the seam.
+     */
+    #[must_use]
+    pub fn build(mut config: WorldConfig) -> Self {
+        let (terrain, plants) = match &config.level {
+            Some(path) => {
+                let level = Level::load(path)
+                    .unwrap_or_else(|e| panic!("Could not load level {}: {}", path, e));
+                config.width = level.width;
+                config.height = level.height;
+                let terrain =
+                    Terrain::from_cells(level.width, level.height, level.cells, &config.terrain);
+                let plants =
+                    PlantLayer::from_fertile(level.width, level.height, &level.fertile, &terrain);
+                (terrain, plants)
+            }
+            None => {
+                let terrain = Terrain::new(config.width, config.height, &config.terrain);
+                let plants = PlantLayer::new(config.width, config.height, &config.plants, &terrain);
+                (terrain, plants)
+            }
+        };
+        if config.movement == MovementModel::Hex
+            && config.topology == Topology::Torus
+            && config.height % 2 == 1
+        {
+            panic!(
+                "Could not build a hex world on a torus with an odd height of {}.",
+                config.height
+            );
+        }
+        Self {
+            config,
+            terrain,
+            plants,
+        }
+    }
 }
 
 impl World {
     #[must_use]
-    pub fn new(
-        width: usize,
-        height: usize,
-        name: String,
-        carrying_capacity: usize,
-        save_history: bool,
-    ) -> Self {
+    pub fn new(config: WorldConfig, save_history: bool, seed: u64) -> Self {
+        Self::from_layers(WorldLayers::build(config), save_history, seed)
+    }
+
+    #[must_use]
+    pub fn from_layers(layers: WorldLayers, save_history: bool, seed: u64) -> Self {
         Self {
-            name,
-            history: Vec::new(),
-            current_state: WorldState::new(),
-            carrying_capacity,
-            width,
-            height,
+            terrain: layers.terrain,
+            history: History::default(),
+            current_state: WorldState::new(layers.plants),
             creature_count: 0,
             save_history,
+            rng: StdRng::seed_from_u64(seed),
+            species: SpeciesTracker::default(),
+            fitness: layers.config.fitness.build(),
+            config: layers.config,
+        }
+    }
+
+    /*
+     * A world put back together from a file. Nothing is read from the level
+     * or plant layout files named in the config.
+     */
+    fn from_parts(config: WorldConfig, terrain: Terrain, state: WorldState) -> Self {
+        let layers = WorldLayers {
+            config,
+            terrain,
+            plants: state.plants.clone(),
+        };
+        let mut world = Self::from_layers(layers, false, 0);
+        world.current_state = state;
+        world
+    }
+
+    /*
+     * The random number generator is not part of a snapshot, so the loaded
+     * world starts from seed 0 until it is reseeded.
+     */
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let snapshot: Snapshot = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "Snapshot version {} is not supported (expected {}).",
+                snapshot.version, SNAPSHOT_VERSION
+            )
+            .into());
+        }
+        let mut world = Self::from_parts(snapshot.config, snapshot.terrain, snapshot.state);
+        world.creature_count = snapshot.creature_count;
+        world.species = SpeciesTracker::from_creatures(&world.current_state.creatures);
+        Ok(world)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            config: self.config.clone(),
+            terrain: self.terrain.clone(),
+            creature_count: self.creature_count,
+            state: self.current_state.clone(),
+        };
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &snapshot)?;
+        Ok(())
+    }
+
+    pub fn load_replay(path: &str) -> Result<Self, Box<dyn Error>> {
+        let replay: Replay = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "Replay version {} is not supported (expected {}).",
+                replay.version, REPLAY_VERSION
+            )
+            .into());
         }
+        let state = if replay.history.is_empty() {
+            WorldState::default()
+        } else {
+            replay.history.frame(0)
+        };
+        let mut world = Self::from_parts(replay.config, replay.terrain, state);
+        world.history = replay.history;
+        Ok(world)
+    }
+
+    pub fn save_replay(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            config: self.config.clone(),
+            terrain: self.terrain.clone(),
+            history: self.history.clone(),
+        };
+        serde_json::to_writer(BufWriter::new(File::create(path)?), &replay)?;
+        Ok(())
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /*
+     * Replaces the fitness built from the config with any other, for
+     * experiments that score creatures in ways FitnessConfig cannot express.
+     */
+    pub fn set_fitness(&mut self, fitness: Box<dyn Fitness>) {
+        self.fitness = fitness;
     }
 
     pub fn add_creature_with_position(&mut self, name: &str, position: Position) {
-        let creature = Creature::new(
+        let direction = self.config.movement.random_direction(&mut self.rng);
+        let mut creature = Creature::new(
             position,
-            Position::new(self.width as i32, self.height as i32),
+            Position::new(self.config.width as i32, self.config.height as i32),
             name,
+            Genome::new(
+                self.config.traits.initial_set(),
+                &self.config.behavior,
+                &mut self.rng,
+            ),
+            direction,
             1,
             self.creature_count as u64,
         );
+        creature.species = self.assign_species(&creature);
         self.creature_count += 1;
         self.current_state.creatures.push(creature);
     }
 
+    fn assign_species(&mut self, creature: &Creature) -> u64 {
+        self.species
+            .assign(&creature.genome, &self.config.species, &self.config.traits)
+    }
+
+    #[must_use]
+    pub fn num_species(&self) -> usize {
+        self.species.len()
+    }
+
     pub fn add_creature(&mut self, name: &str) {
-        self.add_creature_with_position(
-            name,
-            Position::new(self.width as i32 / 2, self.height as i32 / 2),
-        );
+        self.add_creature_with_position(name, self.spawn_position());
     }
 
-    pub fn add_creatures_from_world(&mut self, world: Self) {
-        loop {
-            for creature in &world.current_state.creatures {
-                if creature.is_alive() {
-                    let new_creature = Creature::new_from_old(
-                        creature,
-                        self.creature_count as u64,
-                        Position::new(self.width as i32 / 2, self.height as i32 / 2),
-                        Position::new(self.width as i32, self.height as i32),
-                    );
-                    self.creature_count += 1;
-                    self.current_state.creatures.push(new_creature);
-                    if self.creature_count >= self.carrying_capacity {
-                        return;
-                    }
-                }
+    /*
+     * Moves the creatures of another world into this one, for example from a
+     * snapshot taken with a different configuration. Creatures that would be
+     * outside this world or on a blocked cell are moved to the nearest open
+     * cell, and directions this world cannot face are replaced.
+     */
+    pub fn adopt_creatures(&mut self, world: Self) {
+        let (width, height) = (self.config.width as i32, self.config.height as i32);
+        let movement = self.config.movement;
+        self.creature_count = self.creature_count.max(world.creature_count);
+        for mut creature in world.current_state.creatures {
+            creature.position = self.terrain.nearest_open(Position::new(
+                creature.position.x.clamp(0, width - 1),
+                creature.position.y.clamp(0, height - 1),
+            ));
+            creature.world_bounds = Position::new(width, height);
+            if !movement.directions().contains(&creature.direction) {
+                creature.direction = movement.random_direction(&mut self.rng);
             }
-            if self.creature_count == 0 {
-                return;
+            self.current_state.creatures.push(creature);
+        }
+        self.species = SpeciesTracker::from_creatures(&self.current_state.creatures);
+    }
+
+    fn spawn_position(&self) -> Position {
+        self.terrain.nearest_open(Position::new(
+            self.config.width as i32 / 2,
+            self.config.height as i32 / 2,
+        ))
+    }
+
+    /*
+     * Breeds the creatures of the given world into this one until the
+     * carrying capacity is reached, with parents picked by the selection
+     * strategy of this world. In sexual reproduction the second parents are
+     * picked the same way and paired with the first ones at random, but never
+     * with themselves while another pairing exists. The old creatures are
+     * scored by the fitness of the old world. The new world takes over that
+     * fitness, so one set with set_fitness stays in use, and the random
+     * number generator of the old world, so a run seeded once stays
+     * reproducible across generations. Ids carry on from the old world, so
+     * they are unique across generations.
+     */
+    pub fn add_creatures_from_world(&mut self, world: Self) {
+        self.rng = world.rng;
+        self.species = world.species;
+        self.creature_count = self.creature_count.max(world.creature_count);
+        let spawn_position = self.spawn_position();
+        let creatures = &world.current_state.creatures;
+        let candidates: Vec<Candidate> = creatures
+            .iter()
+            .map(|c| Candidate {
+                score: world.fitness.score(c),
+                alive: c.is_alive(),
+            })
+            .collect();
+        self.fitness = world.fitness;
+        let strategy = self.config.selection.build();
+        let count = self
+            .config
+            .carrying_capacity
+            .saturating_sub(self.current_state.creatures.len());
+        let parents = strategy.select(&candidates, count, &mut self.rng);
+        let mates = match self.config.reproduction {
+            Reproduction::Clonal => Vec::new(),
+            Reproduction::Sexual => {
+                let mut mates = strategy.select(&candidates, count, &mut self.rng);
+                mates.shuffle(&mut self.rng);
+                avoid_selfing(&parents, &mut mates, &mut self.rng);
+                mates
             }
+        };
+        for (i, &parent) in parents.iter().enumerate() {
+            let id = self.creature_count as u64;
+            let mut new_creature = match mates.get(i) {
+                None => Creature::new_from_old(
+                    &creatures[parent],
+                    id,
+                    spawn_position,
+                    &self.config,
+                    &mut self.rng,
+                ),
+                Some(&mate) => Creature::new_from_parents(
+                    &creatures[parent],
+                    &creatures[mate],
+                    id,
+                    spawn_position,
+                    &self.config,
+                    &mut self.rng,
+                ),
+            };
+            new_creature.species = self.assign_species(&new_creature);
+            self.creature_count += 1;
+            self.current_state.creatures.push(new_creature);
         }
+        self.species.update(
+            &self.current_state.creatures,
+            &self.config.species,
+            &self.config.traits,
+        );
     }
 
-    pub fn simulate(&mut self, n: i32) {
+    pub fn simulate(&mut self, n: usize) {
         for _ in 0..n {
             self.step();
         }
     }
 
-    fn step(&mut self) {
+    pub fn step(&mut self) {
         if self.save_history {
-            self.history.push(self.current_state.clone());
+            self.history.push(&self.current_state);
         }
 
-        for creature in &mut self.current_state.creatures {
-            creature.step(plant_is_here(creature.position));
+        let state = &mut self.current_state;
+        let (topology, movement) = (self.config.topology, self.config.movement);
+        let width = self.config.width;
+        let index = |position: Position| position.y as usize * width + position.x as usize;
+        let mut occupied = vec![0_u32; width * self.config.height];
+        for creature in state.creatures.iter().filter(|c| c.is_alive()) {
+            occupied[index(creature.position)] += 1;
         }
-    }
 
-    pub fn display_map(&self, mode: DisplayMode, states: &[WorldState], mut frame_delay: u64) {
-        if mode == DisplayMode::TerminalStatic {
-            let state = &states[states.len() - 1];
-            let mut map = Map::new(self.width, self.height, self.name.to_string());
-            for x in 0..self.width {
-                for y in 0..self.height {
-                    let position = Position::new(x as i32, y as i32);
-                    if plant_is_here(position) {
-                        map.set_plant(position);
-                    }
+        let continuous = &self.config.continuous;
+        let mut births = Vec::new();
+        for i in 0..state.creatures.len() {
+            if !state.creatures[i].is_alive() {
+                continue;
+            }
+            let creature = &mut state.creatures[i];
+            let ahead = creature.ahead(topology, movement);
+            let surroundings = Surroundings {
+                terrain: &self.terrain,
+                traits: &self.config.traits,
+                predation: &self.config.predation,
+                topology,
+                movement,
+                plant_is_here: state.plants.is_here(creature.position, state.tick),
+                plant_ahead: ahead.is_some_and(|p| state.plants.is_here(p, state.tick)),
+                wall_ahead: ahead.is_none_or(|p| self.terrain.get(p).blocks()),
+                creature_ahead: ahead.is_some_and(|p| occupied[index(p)] > 0),
+            };
+            let position = creature.position;
+            let outcome = creature.step(&surroundings, &mut self.rng);
+            if outcome.ate_plant {
+                state
+                    .plants
+                    .consume(position, state.tick + self.config.plants.regrow_ticks);
+            }
+            occupied[index(position)] -= 1;
+            if creature.is_alive() {
+                occupied[index(creature.position)] += 1;
+            }
+
+            let prey_nearby = occupied[index(creature.position)] > 1
+                || ahead.is_some_and(|p| occupied[index(p)] > 0);
+            if outcome.attacked && creature.is_alive() && prey_nearby {
+                let prey = Self::find_prey(&state.creatures, i, ahead);
+                if let Some(j) = prey {
+                    let prey_life = state.creatures[j].life;
+                    state.creatures[j].life = 0.0;
+                    occupied[index(state.creatures[j].position)] -= 1;
+                    state.creatures[i].eat_prey(prey_life, &self.config.predation);
                 }
             }
-            for creature in &state.creatures {
-                map.set_creature(creature.position, creature.direction, creature.life as i32);
+
+            let creature = &mut state.creatures[i];
+            if continuous.enabled && creature.is_alive() && creature.life >= continuous.threshold {
+                let free = creature.free_neighbour(
+                    &self.terrain,
+                    topology,
+                    movement,
+                    |p| occupied[index(p)] > 0,
+                    &mut self.rng,
+                );
+                if let Some(position) = free {
+                    let id = (self.creature_count + births.len()) as u64;
+                    let mut child =
+                        Creature::new_from_old(creature, id, position, &self.config, &mut self.rng);
+                    child.life = continuous.offspring_life;
+                    creature.life = (creature.life - continuous.cost).max(0.0);
+                    occupied[index(position)] += 1;
+                    births.push(child);
+                }
             }
-            map.display();
+        }
+        state.tick += 1;
+
+        if continuous.enabled {
+            for mut child in births {
+                child.species = self.assign_species(&child);
+                self.creature_count += 1;
+                self.current_state.creatures.push(child);
+            }
+        }
+    }
+
+    /*
+     * The index of a living creature for the attacker at index i to eat,
+     * looking first in its own cell and then in the cell it faces.
+     */
+    fn find_prey(creatures: &[Creature], i: usize, ahead: Option<Position>) -> Option<usize> {
+        let in_cell = |position: Position| {
+            (0..creatures.len())
+                .find(|&j| j != i && creatures[j].is_alive() && creatures[j].position == position)
+        };
+        in_cell(creatures[i].position).or_else(|| ahead.and_then(in_cell))
+    }
+
+    /*
+     * Starts a new reporting period of a world in continuous reproduction,
+     * which carries on from where the last period ended. Dead creatures are
+     * dropped, the species are brought up to date and the history starts
+     * over.
+     */
+    pub fn begin_period(&mut self, save_history: bool) {
+        self.current_state.creatures.retain(Creature::is_alive);
+        self.species.update(
+            &self.current_state.creatures,
+            &self.config.species,
+            &self.config.traits,
+        );
+        self.history = History::default();
+        self.save_history = save_history;
+    }
+
+    /*
+     * A line for each creature at the position, followed by its trait
+     * values, for the Info panel of the TUI.
+     */
+    fn inspect(&self, state: &WorldState, position: Position, show_dead: bool) -> Vec<String> {
+        state
+            .get_creatures_at(position)
+            .into_iter()
+            .filter(|creature| creature.is_alive() || show_dead)
+            .map(|creature| {
+                let traits = self.config.traits.format(&creature.genome.trait_set);
+                format!("{} {}", creature, traits)
+            })
+            .collect()
+    }
+
+    fn build_map(&self, state: &WorldState) -> Map {
+        let mut map = Map::new(
+            self.config.width,
+            self.config.height,
+            self.config.name.to_string(),
+        );
+        for creature in &state.creatures {
+            let diet = creature.diet(&self.config.traits, &self.config.predation);
+            map.set_creature(
+                creature.position,
+                creature.direction,
+                diet,
+                creature.life as i32,
+            );
+        }
+        for x in 0..self.config.width {
+            for y in 0..self.config.height {
+                let position = Position::new(x as i32, y as i32);
+                if state.plant_is_here(position) {
+                    map.set_plant(position);
+                }
+                map.set_terrain(position, self.terrain.get(position));
+            }
+        }
+        map
+    }
+
+    pub fn display_map(&self, mode: DisplayMode, history: &History, mut frame_delay: u64) {
+        if history.is_empty() {
+            return;
+        }
+        if mode == DisplayMode::TerminalStatic {
+            let state = history.frame(history.len() - 1);
+            self.build_map(&state).display();
         } else if mode == DisplayMode::TerminalDynamic {
             enable_raw_mode().expect("Could not enable raw mode.");
             let mut stdout = io::stdout();
@@ -175,22 +707,12 @@ impl World {
             let mut show_dead = false;
             let mut cursor = Cursor {
                 show: true,
-                x: self.width as i32 / 2,
-                y: self.height as i32 / 2,
+                x: self.config.width as i32 / 2,
+                y: self.config.height as i32 / 2,
             };
             loop {
-                let mut map = Map::new(self.width, self.height, self.name.to_string());
-                for creature in &states[frame_count].creatures {
-                    map.set_creature(creature.position, creature.direction, creature.life as i32);
-                }
-                for x in 0..self.width {
-                    for y in 0..self.height {
-                        let position = Position::new(x as i32, y as i32);
-                        if plant_is_here(position) {
-                            map.set_plant(position);
-                        }
-                    }
-                }
+                let state = history.frame(frame_count);
+                let map = self.build_map(&state);
                 cursor.show = is_paused;
                 match terminal_graphics::display(
                     &mut terminal,
@@ -198,8 +720,8 @@ impl World {
                     frame_count,
                     frame_delay,
                     &cursor,
-                    &states[frame_count],
-                    show_dead,
+                    &state,
+                    &self.inspect(&state, Position::new(cursor.x, cursor.y), show_dead),
                 ) {
                     Interaction::Halt => break,
                     Interaction::Progress => {
@@ -232,15 +754,13 @@ impl World {
                         cursor.x += 1;
                     }
                     Interaction::SpeedUp => {
-                        frame_delay -= 10;
-                        frame_delay = std::cmp::max(frame_delay, 10);
+                        frame_delay = frame_delay.saturating_sub(10).max(10);
                     }
                     Interaction::SlowDown => {
-                        frame_delay += 10;
-                        frame_delay = std::cmp::min(frame_delay, 1000);
+                        frame_delay = (frame_delay + 10).min(1000);
                     }
                 }
-                if frame_count == states.len() {
+                if frame_count == history.len() {
                     frame_count = 0;
                 }
             }
@@ -252,3 +772,23 @@ impl World {
         }
     }
 }
+
+/*
+ * Swaps mates between pairs so that no creature is paired with itself,
+ * unless no other pairing is possible.
+ */
+fn avoid_selfing(parents: &[usize], mates: &mut [usize], rng: &mut impl Rng) {
+    let n = parents.len().min(mates.len());
+    for i in 0..n {
+        if mates[i] != parents[i] {
+            continue;
+        }
+        let offset = rng.gen_range(0..n);
+        let other = (0..n)
+            .map(|k| (k + offset) % n)
+            .find(|&j| mates[j] != parents[i] && mates[i] != parents[j]);
+        if let Some(j) = other {
+            mates.swap(i, j);
+        }
+    }
+}